@@ -15,6 +15,8 @@ impl StyleRule {
         }
     }
 
+    /// The highest specificity of the selectors, or zero for a rule without
+    /// selectors like the one holding an inline style.
     pub fn specificity(&self) -> Specificity {
         let specificities = self
             .selectors
@@ -22,6 +24,9 @@ impl StyleRule {
             .map(|selector| selector.specificity())
             .collect::<Vec<Specificity>>();
 
-        specificities.into_iter().max().unwrap()
+        specificities
+            .into_iter()
+            .max()
+            .unwrap_or_else(|| Specificity::new(0, 0, 0))
     }
 }
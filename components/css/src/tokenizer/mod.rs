@@ -104,23 +104,15 @@ fn is_start_identifier(value: &str) -> bool {
 
     match chars.next() {
         Some('-') => {
-            let second = chars.next().unwrap();
-            let third = chars.next().unwrap();
-            if is_name_start(second) || second == '-' {
-                return true;
-            }
-            if is_valid_escape(&format!("{}{}", second, third)) {
-                return true;
+            let rest = chars.as_str();
+            match chars.next() {
+                Some(c) => is_name_start(c) || c == '-' || is_valid_escape(rest),
+                None => false,
             }
-            return false;
         }
-        Some(c) if is_name_start(c) => {
-            return true;
-        }
-        Some('\\') => {
-            return is_valid_escape(&format!("{}{}", '\\', chars.next().unwrap()));
-        }
-        _ => return false,
+        Some(c) if is_name_start(c) => true,
+        Some('\\') => is_valid_escape(value),
+        _ => false,
     }
 }
 
@@ -399,14 +391,18 @@ where
 
     fn consume_numeric(&mut self) -> Token {
         let (number, type_) = self.consume_number();
-        if let Some(next_3_chars) = self.input.peek_next_as::<String>(3) {
-            if is_start_identifier(&next_3_chars) {
-                return Token::Dimension {
-                    value: number,
-                    type_,
-                    unit: self.consume_name(),
-                };
-            }
+        // A unit at the end of the input (e.g. in a `style` attribute) is
+        // shorter than 3 characters.
+        let next_3_chars = self
+            .input
+            .peek_next_as::<String>(3)
+            .unwrap_or_else(|| self.input.peek_max().into_iter().collect());
+        if is_start_identifier(&next_3_chars) {
+            return Token::Dimension {
+                value: number,
+                type_,
+                unit: self.consume_name(),
+            };
         }
         if let Some('%') = self.input.peek() {
             self.consume_next();
@@ -712,6 +708,27 @@ mod tests {
         assert_eq!(tokenizer.consume_token(), Token::EOF);
     }
 
+    #[test]
+    fn tokenize_dimension_at_end_of_input() {
+        let css = "margin: 50px".chars();
+        let mut tokenizer = Tokenizer::new(css);
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Ident("margin".to_string())
+        );
+        assert_eq!(tokenizer.consume_token(), Token::Colon);
+        assert_eq!(tokenizer.consume_token(), Token::Whitespace);
+        assert_eq!(
+            tokenizer.consume_token(),
+            Token::Dimension {
+                value: 50.0,
+                type_: NumberType::Integer,
+                unit: "px".to_string()
+            }
+        );
+        assert_eq!(tokenizer.consume_token(), Token::EOF);
+    }
+
     #[test]
     fn tokenize_css_function() {
         let css = r"#id_selector .class_selector {
@@ -1,9 +1,12 @@
 use super::node::{NodeHooks, NodePtr};
 use css::cssom::css_rule::CSSRule;
+use flume::{unbounded, Receiver, Sender};
 use loader::document_loader::DocumentLoader;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::ops::Deref;
+use std::time::Duration;
 use style_types::{ContextualRule, ContextualStyleSheet};
+use url::parser::URLParser;
 use url::Url;
 
 pub struct Document {
@@ -11,9 +14,17 @@ pub struct Document {
     doctype: RefCell<Option<DocumentType>>,
     mode: RefCell<QuirksMode>,
     loader: RefCell<Option<DocumentLoader>>,
-    base: RefCell<Option<Url>>,
+    url: RefCell<Option<Url>>,
+    /// The first `<base>` with an `href`, which sets the base URL of the document.
+    base_element: RefCell<Option<NodePtr>>,
+    /// The encoding the document was decoded with, inherited by its stylesheets.
+    charset: RefCell<String>,
     style_elements: RefCell<Vec<NodePtr>>,
     user_agent_stylesheet: RefCell<Option<ContextualStyleSheet>>,
+    stylesheet_parse_time: Cell<Duration>,
+    pending_stylesheets: Cell<usize>,
+    /// Receives the parse time of every external stylesheet once its load has settled.
+    stylesheet_loads: (Sender<Duration>, Receiver<Duration>),
 }
 
 pub struct DocumentType {
@@ -44,9 +55,14 @@ impl Document {
             doctype: RefCell::new(None),
             mode: RefCell::new(QuirksMode::NoQuirks),
             loader: RefCell::new(None),
-            base: RefCell::new(None),
+            url: RefCell::new(None),
+            base_element: RefCell::new(None),
+            charset: RefCell::new("utf-8".to_string()),
             style_elements: RefCell::new(Vec::new()),
             user_agent_stylesheet: RefCell::new(None),
+            stylesheet_parse_time: Cell::new(Duration::ZERO),
+            pending_stylesheets: Cell::new(0),
+            stylesheet_loads: unbounded(),
         }
     }
 
@@ -82,6 +98,58 @@ impl Document {
         self.user_agent_stylesheet.borrow_mut().replace(stylesheet);
     }
 
+    /// Accumulate the time spent parsing stylesheets of this document, which
+    /// happens while the document is being built.
+    pub fn add_stylesheet_parse_time(&self, duration: Duration) {
+        self.stylesheet_parse_time
+            .set(self.stylesheet_parse_time.get() + duration);
+    }
+
+    pub fn stylesheet_parse_time(&self) -> Duration {
+        self.stylesheet_parse_time.get()
+    }
+
+    /// Register an external stylesheet load. The returned sender must receive the
+    /// parse time of the stylesheet once the load has finished, failed or was
+    /// given up on, as the document keeps waiting for it otherwise.
+    pub fn start_stylesheet_load(&self) -> Sender<Duration> {
+        self.pending_stylesheets
+            .set(self.pending_stylesheets.get() + 1);
+        self.stylesheet_loads.0.clone()
+    }
+
+    pub fn pending_stylesheets(&self) -> usize {
+        self.pending_stylesheets.get()
+    }
+
+    /// Wait until one of the pending stylesheet loads settles.
+    pub async fn wait_for_stylesheet(&self) {
+        if self.pending_stylesheets() == 0 {
+            return;
+        }
+
+        if let Ok(parse_time) = self.stylesheet_loads.1.recv_async().await {
+            self.finish_stylesheet_load(parse_time);
+        }
+    }
+
+    /// Account for the stylesheet loads that settled without waiting for the
+    /// others, and return how many did.
+    pub fn take_loaded_stylesheets(&self) -> usize {
+        let mut loaded_count = 0;
+        while let Ok(parse_time) = self.stylesheet_loads.1.try_recv() {
+            self.finish_stylesheet_load(parse_time);
+            loaded_count += 1;
+        }
+        loaded_count
+    }
+
+    fn finish_stylesheet_load(&self, parse_time: Duration) {
+        self.add_stylesheet_parse_time(parse_time);
+        self.pending_stylesheets
+            .set(self.pending_stylesheets.get().saturating_sub(1));
+    }
+
     pub fn register_style_element(&self, element: NodePtr) {
         self.style_elements.borrow_mut().push(element);
     }
@@ -132,12 +200,49 @@ impl Document {
         style_rules
     }
 
+    /// The URL the document was loaded from, which is its base URL unless a
+    /// `<base href>` overrides it.
+    pub fn url(&self) -> Option<Url> {
+        self.url.borrow().deref().clone()
+    }
+
+    pub fn set_url(&self, url: Option<Url>) {
+        *self.url.borrow_mut() = url;
+    }
+
+    /// Elements are inserted in tree order while parsing, so only the first
+    /// `<base href>` to be inserted is kept.
+    pub fn register_base_element(&self, element: NodePtr) {
+        let mut base_element = self.base_element.borrow_mut();
+        if base_element.is_none() {
+            *base_element = Some(element);
+        }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/urls-and-fetching.html#document-base-url
     pub fn base(&self) -> Option<Url> {
-        self.base.borrow().deref().clone()
+        let base_element = self.base_element.borrow();
+        match base_element
+            .as_ref()
+            .map(|element| element.as_element().data())
+        {
+            Some(crate::elements::ElementData::Base(base)) => base.frozen_base_url(),
+            _ => self.url(),
+        }
+    }
+
+    /// Resolve a URL found in the document, e.g. in an `href`, against its base URL.
+    /// https://html.spec.whatwg.org/multipage/urls-and-fetching.html#encoding-parsing-a-url
+    pub fn parse_url(&self, input: &str) -> Option<Url> {
+        URLParser::parse(input, self.base())
+    }
+
+    pub fn charset(&self) -> String {
+        self.charset.borrow().clone()
     }
 
-    pub fn set_base(&self, base: Option<Url>) {
-        *self.base.borrow_mut() = base;
+    pub fn set_charset(&self, charset: &str) {
+        *self.charset.borrow_mut() = charset.to_string();
     }
 }
 
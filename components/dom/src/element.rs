@@ -3,6 +3,10 @@ use crate::node::{ChildrenUpdateContext, InsertContext};
 use super::dom_token_list::DOMTokenList;
 use super::elements::{ElementData, ElementMethods};
 use super::node::NodeHooks;
+use css::parser::structs::{Declaration, DeclarationOrAtRule};
+use css::parser::Parser;
+use css::tokenizer::token::Token;
+use css::tokenizer::Tokenizer;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
@@ -14,6 +18,7 @@ pub struct Element {
     attributes: RefCell<AttributeMap>,
     id: RefCell<Option<String>>,
     class_list: RefCell<DOMTokenList>,
+    inline_style: RefCell<Vec<Declaration>>,
     data: ElementData,
 }
 
@@ -79,6 +84,7 @@ impl Element {
             attributes: RefCell::new(AttributeMap::new()),
             id: RefCell::new(None),
             class_list: RefCell::new(DOMTokenList::new()),
+            inline_style: RefCell::new(Vec::new()),
             data,
         }
     }
@@ -96,6 +102,9 @@ impl Element {
             *self.class_list.borrow_mut() = DOMTokenList::from(value);
             return;
         }
+        if name == "style" {
+            *self.inline_style.borrow_mut() = parse_inline_style(value);
+        }
         self.attributes
             .borrow_mut()
             .insert(name.to_owned(), value.to_owned());
@@ -118,6 +127,11 @@ impl Element {
         self.id.borrow().clone()
     }
 
+    /// Declarations of the `style` attribute, in the order they were written.
+    pub fn inline_style(&self) -> Vec<Declaration> {
+        self.inline_style.borrow().clone()
+    }
+
     pub fn handle_on_inserted(&self, context: InsertContext) {
         self.data.handle_on_inserted(context);
     }
@@ -130,3 +144,17 @@ impl Element {
         &self.data
     }
 }
+
+fn parse_inline_style(style: &str) -> Vec<Declaration> {
+    let tokenizer = Tokenizer::new(style.chars());
+    let mut parser = Parser::<Token>::new(tokenizer.run());
+
+    parser
+        .parse_a_list_of_declarations()
+        .into_iter()
+        .filter_map(|declaration| match declaration {
+            DeclarationOrAtRule::Declaration(declaration) => Some(declaration),
+            _ => None,
+        })
+        .collect()
+}
@@ -23,6 +23,7 @@ pub fn create_element(document: WeakTreeNode<Node>, tag_name: &str) -> NodePtr {
     let node = translate!(tag_name, {
         "html" => Html > HTMLHtmlElement,
         "head" => Head > HTMLHeadElement,
+        "base" => Base > HTMLBaseElement,
         "title" => Title > HTMLTitleElement,
         "body" => Body > HTMLBodyElement,
         "div" => Div > HTMLDivElement,
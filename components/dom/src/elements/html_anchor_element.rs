@@ -4,7 +4,6 @@ use super::ElementHooks;
 use super::ElementMethods;
 use crate::node::InsertContext;
 use crate::node::NodeHooks;
-use url::parser::URLParser;
 use url::Url;
 
 #[derive(Debug)]
@@ -25,11 +24,9 @@ impl ElementHooks for HTMLAnchorElement {}
 impl NodeHooks for HTMLAnchorElement {
     fn on_inserted(&self, context: InsertContext) {
         let document = context.document;
-        let base = document.as_document().base();
-
         let element = context.current_node.as_element();
         let href_str = element.attributes().borrow().get_str("href");
-        *self.href.borrow_mut() = URLParser::parse(&href_str, base);
+        *self.href.borrow_mut() = document.as_document().parse_url(&href_str);
     }
 }
 
@@ -0,0 +1,54 @@
+use std::cell::RefCell;
+
+use super::ElementHooks;
+use super::ElementMethods;
+use crate::node::InsertContext;
+use crate::node::NodeHooks;
+use url::parser::URLParser;
+use url::Url;
+
+#[derive(Debug)]
+pub struct HTMLBaseElement {
+    frozen_base_url: RefCell<Option<Url>>,
+}
+
+impl HTMLBaseElement {
+    pub fn empty() -> Self {
+        Self {
+            frozen_base_url: RefCell::new(None),
+        }
+    }
+
+    pub fn frozen_base_url(&self) -> Option<Url> {
+        self.frozen_base_url.borrow().clone()
+    }
+}
+
+impl ElementHooks for HTMLBaseElement {}
+
+impl NodeHooks for HTMLBaseElement {
+    /// Freeze the base URL by resolving `href` against the URL of the document,
+    /// which stays the base URL if `href` is not a valid URL.
+    /// https://html.spec.whatwg.org/multipage/semantics.html#set-the-frozen-base-url
+    fn on_inserted(&self, context: InsertContext) {
+        let element = context.current_node.as_element();
+        if !element.has_attribute("href") {
+            return;
+        }
+
+        let document = context.document.as_document();
+        let fallback_base_url = document.url();
+        let href_str = element.attributes().borrow().get_str("href");
+        let frozen_base_url =
+            URLParser::parse(&href_str, fallback_base_url.clone()).or(fallback_base_url);
+
+        *self.frozen_base_url.borrow_mut() = frozen_base_url;
+        document.register_base_element(context.current_node);
+    }
+}
+
+impl ElementMethods for HTMLBaseElement {
+    fn tag_name(&self) -> String {
+        "base".to_string()
+    }
+}
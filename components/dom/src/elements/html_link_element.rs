@@ -1,29 +1,70 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
 
 use super::ElementHooks;
 use super::ElementMethods;
 use crate::node::InsertContext;
 use crate::node::NodeHooks;
 use crate::node::NodePtr;
-use flume::bounded;
 use flume::Sender;
-use loader::resource_loop::request::FetchListener;
+use loader::resource_loop::error::LoadError;
+use loader::resource_loop::request::{Destination, FetchListener, LoadRequest};
+use loader::resource_loop::response::Response;
 use shared::byte_string::ByteString;
 use style_types::ContextualStyleSheet;
 use url::Url;
 
 use css::parser::Parser;
 use css::tokenizer::{token::Token, Tokenizer};
-use url::parser::URLParser;
 
 struct StyleLoaderContext {
-    stylesheet_tx: Sender<ContextualStyleSheet>,
+    stylesheet: Arc<Mutex<Option<ContextualStyleSheet>>>,
+    done_tx: Sender<Duration>,
+    settled: AtomicBool,
+    document_charset: String,
+}
+
+impl StyleLoaderContext {
+    /// Report the load as settled to the document, once.
+    fn settle(&self, parse_time: Duration) {
+        if self.settled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // The document might have been unloaded while the stylesheet was loading.
+        let _ = self.done_tx.send(parse_time);
+    }
+}
+
+/// A listener dropped without hearing back from the fetch, e.g. because the
+/// resource loop went away, must not leave the document waiting for it.
+impl Drop for StyleLoaderContext {
+    fn drop(&mut self) {
+        self.settle(Duration::ZERO);
+    }
 }
 
 impl FetchListener for StyleLoaderContext {
-    fn on_finished(&self, bytes: loader::resource_loop::request::Bytes) {
-        let css = ByteString::new(&bytes);
+    fn on_finished(&self, response: Response) {
+        if !response.is_success() {
+            log::error!(
+                "Unable to load CSS from {}: {} {}",
+                response.url,
+                response.status,
+                response.status_text
+            );
+            self.settle(Duration::ZERO);
+            return;
+        }
+
+        let css = ByteString::from_css(
+            &response.body,
+            response.charset().as_deref(),
+            Some(&self.document_charset),
+        );
+        let start = Instant::now();
         let tokenizer = Tokenizer::new(css.chars());
         let mut parser = Parser::<Token>::new(tokenizer.run());
         let stylesheet = parser.parse_a_css_stylesheet();
@@ -34,11 +75,16 @@ impl FetchListener for StyleLoaderContext {
             style_types::CSSLocation::External,
         );
 
-        self.stylesheet_tx.send(stylesheet).unwrap();
+        self.stylesheet.lock().unwrap().replace(stylesheet);
+        self.settle(start.elapsed());
     }
 
-    fn on_errored(&self, error: loader::resource_loop::error::LoadError) {
-        log::error!("Unable to load CSS: {}", error);
+    fn on_errored(&self, error: LoadError) {
+        match error {
+            LoadError::Aborted => log::debug!("Stylesheet load aborted"),
+            error => log::error!("Unable to load CSS: {}", error),
+        }
+        self.settle(Duration::ZERO);
     }
 }
 
@@ -54,23 +100,22 @@ impl HTMLLinkElement {
         }
     }
 
+    /// Start loading the stylesheet without waiting for it. The document keeps
+    /// track of the load until the stylesheet has been parsed.
     pub fn load_stylesheet(&self, url: &Url, document: NodePtr) {
         log::info!("Loading stylesheet from: {}", url);
 
-        let stylesheet = self.stylesheet.clone();
-        let (tx, rx) = bounded(1);
-
-        let loader = document.as_document().loader();
-        loader.fetch(url.clone(), StyleLoaderContext { stylesheet_tx: tx });
-
-        // This is blocking the main thread manually. In the future, this receiving should run on a separate thread
-        // and the main thread should wait for that thread to finish, while working on other things.
-        match rx.recv() {
-            Ok(sheet) => {
-                stylesheet.lock().unwrap().replace(sheet);
-            }
-            _ => {}
-        }
+        let document = document.as_document();
+        let listener = StyleLoaderContext {
+            stylesheet: self.stylesheet.clone(),
+            done_tx: document.start_stylesheet_load(),
+            settled: AtomicBool::new(false),
+            document_charset: document.charset(),
+        };
+        let request = LoadRequest::new(url.clone(), Arc::new(listener))
+            .with_header("Accept", "text/css,*/*;q=0.1")
+            .with_destination(Destination::Style);
+        document.loader().load(request);
     }
 
     pub fn stylesheet(&self) -> Arc<Mutex<Option<ContextualStyleSheet>>> {
@@ -89,7 +134,7 @@ impl NodeHooks for HTMLLinkElement {
         let href_str = attrs.borrow().get_str("href");
         let rel_str = attrs.borrow().get_str("rel");
 
-        let href_url = URLParser::parse(&href_str, document.as_document().base());
+        let href_url = document.as_document().parse_url(&href_str);
         match href_url {
             Some(url) => match rel_str.as_str() {
                 "stylesheet" => {
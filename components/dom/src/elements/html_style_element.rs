@@ -1,5 +1,6 @@
 use std::cell::Ref;
 use std::cell::RefCell;
+use std::time::Instant;
 
 use css::parser::Parser;
 use css::tokenizer::token::Token;
@@ -40,6 +41,7 @@ impl NodeHooks for HTMLStyleElement {
 
     fn on_children_updated(&self, context: ChildrenUpdateContext) {
         let css = context.current_node.descendant_text_content();
+        let start = Instant::now();
         let tokenizer = Tokenizer::new(css.chars());
         let mut parser = Parser::<Token>::new(tokenizer.run());
         let stylesheet = parser.parse_a_css_stylesheet();
@@ -50,6 +52,11 @@ impl NodeHooks for HTMLStyleElement {
             style_types::CSSLocation::Embedded,
         );
 
+        context
+            .document
+            .as_document()
+            .add_stylesheet_parse_time(start.elapsed());
+
         self.stylesheet.replace(Some(stylesheet));
     }
 }
@@ -4,6 +4,7 @@ use super::node::NodeHooks;
 use enum_dispatch::enum_dispatch;
 
 mod html_anchor_element;
+mod html_base_element;
 mod html_body_element;
 mod html_div_element;
 mod html_head_element;
@@ -14,6 +15,7 @@ mod html_title_element;
 mod html_unknown_element;
 
 pub use html_anchor_element::*;
+pub use html_base_element::*;
 pub use html_body_element::*;
 pub use html_div_element::*;
 pub use html_head_element::*;
@@ -27,6 +29,7 @@ pub use html_unknown_element::*;
 #[derive(Debug)]
 pub enum ElementData {
     Anchor(HTMLAnchorElement),
+    Base(HTMLBaseElement),
     Body(HTMLBodyElement),
     Div(HTMLDivElement),
     Head(HTMLHeadElement),
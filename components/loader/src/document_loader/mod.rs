@@ -2,22 +2,61 @@ use flume::Sender;
 use std::sync::Arc;
 use url::Url;
 
-use crate::resource_loop::request::{FetchListener, LoadRequest};
+use crate::resource_loop::{
+    cache::CacheMode,
+    request::{AbortHandle, FetchListener, LoadRequest},
+};
 
 #[derive(Clone)]
 pub struct DocumentLoader {
     resource_loop_tx: Sender<LoadRequest>,
+    cache_mode: CacheMode,
+    abort_handle: AbortHandle,
 }
 
 impl DocumentLoader {
     pub fn new(resource_loop_tx: Sender<LoadRequest>) -> Self {
-        Self { resource_loop_tx }
+        Self {
+            resource_loop_tx,
+            cache_mode: CacheMode::Default,
+            abort_handle: AbortHandle::new(),
+        }
+    }
+
+    /// Cache mode applied to every request of the document that doesn't choose
+    /// its own, e.g. to revalidate all subresources when the page is reloaded.
+    pub fn with_cache_mode(mut self, cache_mode: CacheMode) -> Self {
+        self.cache_mode = cache_mode;
+        self
+    }
+
+    /// Share the abort handle of the document with other requests, e.g. the
+    /// request of the document itself.
+    pub fn with_abort_handle(mut self, abort_handle: AbortHandle) -> Self {
+        self.abort_handle = abort_handle;
+        self
     }
 
     pub fn fetch(&self, url: Url, listener: impl FetchListener + 'static) {
-        let request = LoadRequest::new(url, Arc::new(listener));
+        self.load(LoadRequest::new(url, Arc::new(listener)));
+    }
+
+    pub fn load(&self, mut request: LoadRequest) {
+        if request.cache_mode() == CacheMode::Default {
+            request = request.with_cache_mode(self.cache_mode);
+        }
+        let request = request.with_abort_handle(self.abort_handle.clone());
         self.resource_loop_tx
             .send(request)
             .expect("Unable to send fetch request");
     }
+
+    /// Cancel every request of the document, e.g. when navigating away from it.
+    pub fn abort(&self) {
+        self.abort_handle.abort();
+    }
+
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.abort_handle.clone()
+    }
 }
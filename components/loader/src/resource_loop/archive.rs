@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use url::{parser::URLParser, Url};
+
+use super::response::Response;
+
+/// Whether an archive captures responses from the network or stands in for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveMode {
+    /// Fetch from the network and append every response to the archive.
+    Record,
+    /// Serve responses from the archive without any network access.
+    Replay,
+}
+
+/// A file of remote responses, keyed by request method & URL. Recording one
+/// run of a page and replaying it later renders the page without a network.
+///
+/// Each entry is made of the request line, the final URL, the status line and
+/// the headers, followed by an empty line, the body length and the body.
+#[derive(Clone)]
+pub struct NetworkArchive {
+    mode: ArchiveMode,
+    path: PathBuf,
+    entries: Arc<Mutex<HashMap<String, ArchivedResponse>>>,
+}
+
+#[derive(Clone)]
+struct ArchivedResponse {
+    url: String,
+    status: u16,
+    status_text: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl NetworkArchive {
+    /// Start recording to the given file, replacing any previous archive.
+    pub fn record(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        File::create(&path)?;
+
+        Ok(Self {
+            mode: ArchiveMode::Record,
+            path,
+            entries: Default::default(),
+        })
+    }
+
+    /// Load a recorded archive to serve its responses.
+    pub fn replay(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut reader = BufReader::new(File::open(&path)?);
+
+        let mut entries = HashMap::new();
+        while let Some((key, response)) = read_entry(&mut reader)? {
+            entries.insert(key, response);
+        }
+
+        Ok(Self {
+            mode: ArchiveMode::Replay,
+            path,
+            entries: Arc::new(Mutex::new(entries)),
+        })
+    }
+
+    pub fn mode(&self) -> ArchiveMode {
+        self.mode
+    }
+
+    /// The archived response of a request, with `request_url` as a fallback if
+    /// the final URL of the response cannot be parsed.
+    pub fn get(&self, method: &str, request_url: &Url) -> Option<Response> {
+        let entries = self.entries.lock().unwrap();
+        let archived = entries.get(&entry_key(method, &request_url.as_str()))?;
+
+        Some(Response {
+            url: URLParser::parse(&archived.url, None).unwrap_or_else(|| request_url.clone()),
+            status: archived.status,
+            status_text: archived.status_text.clone(),
+            headers: archived.headers.clone(),
+            body: archived.body.clone(),
+        })
+    }
+
+    /// Append a response to the archive. Failing to write the archive is not an
+    /// error for the request, so it is only logged.
+    pub fn store(&self, method: &str, request_url: &Url, response: &Response) {
+        let key = entry_key(method, &request_url.as_str());
+        let archived = ArchivedResponse {
+            url: response.url.as_str(),
+            status: response.status,
+            status_text: response.status_text.clone(),
+            headers: response.headers.clone(),
+            body: response.body.clone(),
+        };
+
+        // Hold the lock while writing so that concurrent fetches don't
+        // interleave their entries.
+        let mut entries = self.entries.lock().unwrap();
+        if let Err(e) = self.append_entry(&key, &archived) {
+            log::warn!("Unable to archive {}: {}", key, e);
+        }
+        entries.insert(key, archived);
+    }
+
+    fn append_entry(&self, key: &str, response: &ArchivedResponse) -> io::Result<()> {
+        let mut entry = format!(
+            "{}\n{}\n{} {}\n",
+            key, response.url, response.status, response.status_text
+        );
+        for (name, value) in &response.headers {
+            entry.push_str(&format!("{}: {}\n", name, value));
+        }
+        entry.push_str(&format!("\n{}\n", response.body.len()));
+
+        let mut bytes = entry.into_bytes();
+        bytes.extend_from_slice(&response.body);
+        bytes.push(b'\n');
+
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        file.write_all(&bytes)
+    }
+}
+
+fn entry_key(method: &str, url: &str) -> String {
+    format!("{} {}", method.to_uppercase(), url)
+}
+
+fn read_entry(reader: &mut impl BufRead) -> io::Result<Option<(String, ArchivedResponse)>> {
+    let key = match read_line(reader)? {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+
+    let url = read_line(reader)?.ok_or_else(truncated)?;
+    let status_line = read_line(reader)?.ok_or_else(truncated)?;
+    let (status, status_text) = status_line.split_once(' ').unwrap_or((&status_line, ""));
+    let status = status
+        .parse()
+        .map_err(|_| invalid(format!("invalid status line: {}", status_line)))?;
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?.ok_or_else(truncated)?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(": ")
+            .ok_or_else(|| invalid(format!("invalid header: {}", line)))?;
+        headers.push((name.to_string(), value.to_string()));
+    }
+
+    let length_line = read_line(reader)?.ok_or_else(truncated)?;
+    let length = length_line
+        .parse()
+        .map_err(|_| invalid(format!("invalid body length: {}", length_line)))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    read_line(reader)?;
+
+    let response = ArchivedResponse {
+        url,
+        status,
+        status_text: status_text.to_string(),
+        headers,
+        body,
+    };
+    Ok(Some((key, response)))
+}
+
+/// Read a line without its line ending, or `None` at the end of the archive.
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()))
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "truncated archive entry")
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_and_replay() {
+        let path = std::env::temp_dir()
+            .join("moon-archive-test")
+            .join("page.archive");
+        let url = URLParser::parse("https://example.com/page", None).unwrap();
+        let final_url = URLParser::parse("https://example.com/page/", None).unwrap();
+
+        let archive = NetworkArchive::record(&path).unwrap();
+        let mut response = Response::ok(final_url.clone(), b"<p>one\ntwo</p>\n".to_vec());
+        response
+            .headers
+            .push(("Content-Type".to_string(), "text/html".to_string()));
+        archive.store("GET", &url, &response);
+        archive.store("POST", &url, &Response::ok(url.clone(), Vec::new()));
+
+        let archive = NetworkArchive::replay(&path).unwrap();
+        assert_eq!(archive.mode(), ArchiveMode::Replay);
+
+        let replayed = archive.get("GET", &url).unwrap();
+        assert_eq!(replayed.url.as_str(), final_url.as_str());
+        assert_eq!(replayed.status, 200);
+        assert_eq!(replayed.mime_type(), Some("text/html".to_string()));
+        assert_eq!(replayed.body, b"<p>one\ntwo</p>\n");
+
+        assert!(archive.get("POST", &url).unwrap().body.is_empty());
+        assert!(archive.get("GET", &final_url).is_none());
+    }
+}
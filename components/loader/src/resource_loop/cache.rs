@@ -0,0 +1,367 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::request::Bytes;
+
+/// How a request interacts with the HTTP cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Use fresh cached responses and revalidate stale ones.
+    Default,
+    /// Revalidate cached responses with the server even if they are fresh.
+    Revalidate,
+    /// Go to the network without looking into the cache, then store the response.
+    Reload,
+}
+
+/// A response stored in the cache together with the headers that control its
+/// freshness and revalidation.
+pub struct CachedResponse {
+    pub url: String,
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Bytes,
+    stored_at: SystemTime,
+}
+
+/// HTTP cache storing responses on disk, keyed by URL. Each entry is made of a
+/// `<key>.meta` file holding the status line and headers and a `<key>.body` file.
+#[derive(Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+}
+
+impl HttpCache {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The cache in the cache directory of the current user.
+    pub fn default_location() -> Self {
+        let dir = dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("moon")
+            .join("http");
+        Self::new(dir)
+    }
+
+    pub fn get(&self, url: &str) -> Option<CachedResponse> {
+        let key = cache_key(url);
+        let meta = std::fs::read_to_string(self.dir.join(format!("{}.meta", key))).ok()?;
+        let body = std::fs::read(self.dir.join(format!("{}.body", key))).ok()?;
+
+        let mut lines = meta.lines();
+        let stored_url = lines.next()?;
+        if stored_url != url {
+            return None;
+        }
+
+        let (status, status_text) = lines.next()?.split_once(' ')?;
+        let stored_at = UNIX_EPOCH + Duration::from_secs(lines.next()?.parse().ok()?);
+        let headers = lines
+            .filter_map(|line| line.split_once(": "))
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        Some(CachedResponse {
+            url: url.to_string(),
+            status: status.parse().ok()?,
+            status_text: status_text.to_string(),
+            headers,
+            body,
+            stored_at,
+        })
+    }
+
+    /// Store a response unless its headers forbid it or it could never be used
+    /// again. Failing to write the cache is not an error for the request, so it
+    /// is only logged.
+    pub fn put(
+        &self,
+        url: &str,
+        status: u16,
+        status_text: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) {
+        if status != 200 || !is_storable(headers) {
+            return;
+        }
+
+        if let Err(e) = self.write_entry(url, status, status_text, headers, body) {
+            log::warn!("Unable to cache {}: {}", url, e);
+        }
+    }
+
+    /// Refresh the stored headers after the server confirmed that the cached
+    /// body is still valid with a `304 Not Modified`.
+    pub fn update(&self, cached: &CachedResponse, headers: &[(String, String)]) -> CachedResponse {
+        let mut merged = cached.headers.clone();
+        for (name, value) in headers {
+            merged.retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+            merged.push((name.clone(), value.clone()));
+        }
+
+        self.put(
+            &cached.url,
+            cached.status,
+            &cached.status_text,
+            &merged,
+            &cached.body,
+        );
+
+        CachedResponse {
+            url: cached.url.clone(),
+            status: cached.status,
+            status_text: cached.status_text.clone(),
+            headers: merged,
+            body: cached.body.clone(),
+            stored_at: SystemTime::now(),
+        }
+    }
+
+    fn write_entry(
+        &self,
+        url: &str,
+        status: u16,
+        status_text: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let stored_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut meta = format!("{}\n{} {}\n{}\n", url, status, status_text, stored_at);
+        for (name, value) in headers {
+            meta.push_str(&format!("{}: {}\n", name, value));
+        }
+
+        // Write to temporary files first so that concurrent readers never see a
+        // partially written entry.
+        let key = cache_key(url);
+        let meta_path = self.dir.join(format!("{}.meta", key));
+        let body_path = self.dir.join(format!("{}.body", key));
+        let tmp_suffix = format!("{:?}.tmp", std::thread::current().id());
+        let tmp_meta_path = meta_path.with_extension(format!("meta.{}", tmp_suffix));
+        let tmp_body_path = body_path.with_extension(format!("body.{}", tmp_suffix));
+
+        std::fs::write(&tmp_body_path, body)?;
+        std::fs::write(&tmp_meta_path, meta)?;
+        std::fs::rename(tmp_body_path, body_path)?;
+        std::fs::rename(tmp_meta_path, meta_path)
+    }
+}
+
+impl CachedResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the response can be used without asking the server, according to
+    /// `Cache-Control: max-age`, `no-cache` or `Expires`.
+    pub fn is_fresh(&self) -> bool {
+        let age = SystemTime::now()
+            .duration_since(self.stored_at)
+            .unwrap_or_default();
+        age < self.freshness_lifetime()
+    }
+
+    /// Headers turning a request into a conditional request for this response.
+    pub fn validators(&self) -> Vec<(String, String)> {
+        let mut validators = Vec::new();
+        if let Some(etag) = self.header("etag") {
+            validators.push(("If-None-Match".to_string(), etag.to_string()));
+        }
+        if let Some(last_modified) = self.header("last-modified") {
+            validators.push(("If-Modified-Since".to_string(), last_modified.to_string()));
+        }
+        validators
+    }
+
+    fn freshness_lifetime(&self) -> Duration {
+        if has_directive(&self.headers, "no-cache") {
+            return Duration::ZERO;
+        }
+
+        let max_age = directive_value(&self.headers, "max-age")
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(Duration::from_secs);
+        if let Some(max_age) = max_age {
+            return max_age;
+        }
+
+        let expires = self
+            .header("expires")
+            .and_then(|value| httpdate::parse_http_date(value).ok());
+        let date = self
+            .header("date")
+            .and_then(|value| httpdate::parse_http_date(value).ok())
+            .unwrap_or(self.stored_at);
+
+        expires
+            .and_then(|expires| expires.duration_since(date).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Responses that vary on request headers would need one entry per variant,
+/// private ones must not land in a cache on disk, and responses with neither a
+/// freshness lifetime nor a validator would never be served from the cache.
+fn is_storable(headers: &[(String, String)]) -> bool {
+    let has_header = |name: &str| {
+        headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case(name))
+    };
+
+    if has_directive(headers, "no-store") || has_directive(headers, "private") || has_header("vary")
+    {
+        return false;
+    }
+
+    has_directive(headers, "max-age")
+        || has_header("expires")
+        || has_header("etag")
+        || has_header("last-modified")
+}
+
+fn cache_control_directives(headers: &[(String, String)]) -> Vec<(String, Option<String>)> {
+    headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("cache-control"))
+        .flat_map(|(_, value)| value.split(','))
+        .map(|directive| match directive.split_once('=') {
+            Some((name, value)) => (
+                name.trim().to_lowercase(),
+                Some(value.trim().trim_matches('"').to_string()),
+            ),
+            None => (directive.trim().to_lowercase(), None),
+        })
+        .collect()
+}
+
+fn has_directive(headers: &[(String, String)], name: &str) -> bool {
+    cache_control_directives(headers)
+        .iter()
+        .any(|(directive, _)| directive == name)
+}
+
+fn directive_value(headers: &[(String, String)], name: &str) -> Option<String> {
+    cache_control_directives(headers)
+        .into_iter()
+        .find(|(directive, _)| directive == name)
+        .and_then(|(_, value)| value)
+}
+
+/// FNV-1a hash of the URL, stable across runs and platforms.
+fn cache_key(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn temp_cache(name: &str) -> HttpCache {
+        let dir = std::env::temp_dir().join(format!("moon-cache-test-{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        HttpCache::new(dir)
+    }
+
+    #[test]
+    fn store_and_read_response() {
+        let cache = temp_cache("store");
+        let url = "https://example.com/style.css";
+        cache.put(
+            url,
+            200,
+            "OK",
+            &headers(&[("Cache-Control", "max-age=3600"), ("ETag", "\"abc\"")]),
+            b"body { color: red; }",
+        );
+
+        let cached = cache.get(url).unwrap();
+        assert_eq!(cached.body, b"body { color: red; }");
+        assert!(cached.is_fresh());
+        assert_eq!(
+            cached.validators(),
+            headers(&[("If-None-Match", "\"abc\"")])
+        );
+        assert!(cache.get("https://example.com/other.css").is_none());
+    }
+
+    #[test]
+    fn respect_no_store_and_no_cache() {
+        let cache = temp_cache("directives");
+        cache.put(
+            "https://example.com/a",
+            200,
+            "OK",
+            &headers(&[("Cache-Control", "no-store")]),
+            b"a",
+        );
+        cache.put(
+            "https://example.com/b",
+            200,
+            "OK",
+            &headers(&[("Cache-Control", "no-cache, max-age=3600")]),
+            b"b",
+        );
+
+        assert!(cache.get("https://example.com/a").is_none());
+        assert!(!cache.get("https://example.com/b").unwrap().is_fresh());
+    }
+
+    #[test]
+    fn skip_responses_that_cannot_be_reused() {
+        let cache = temp_cache("storable");
+        let cases = [
+            (
+                "vary",
+                vec![("Cache-Control", "max-age=60"), ("Vary", "Cookie")],
+            ),
+            ("private", vec![("Cache-Control", "private, max-age=60")]),
+            (
+                "no-freshness-nor-validator",
+                vec![("Content-Type", "text/css")],
+            ),
+        ];
+
+        for (path, response_headers) in &cases {
+            let url = format!("https://example.com/{}", path);
+            cache.put(&url, 200, "OK", &headers(response_headers), b"body");
+            assert!(cache.get(&url).is_none(), "{}", path);
+        }
+
+        let url = "https://example.com/validator-only";
+        cache.put(
+            url,
+            200,
+            "OK",
+            &headers(&[("Last-Modified", "Tue, 15 Nov 1994 12:45:26 GMT")]),
+            b"body",
+        );
+        assert!(!cache.get(url).unwrap().is_fresh());
+    }
+}
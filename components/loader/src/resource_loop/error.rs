@@ -4,6 +4,7 @@ pub enum LoadError {
     IOError(String),
     InvalidURL(String),
     LoaderDisconnected,
+    Aborted,
 }
 
 impl std::fmt::Display for LoadError {
@@ -26,6 +27,7 @@ impl LoadError {
             LoadError::LoaderDisconnected => {
                 format!("ResourceLoader disconnected unexpectedly.")
             }
+            LoadError::Aborted => "The request was aborted.".to_string(),
         }
     }
 }
@@ -3,8 +3,12 @@
 /// for main process & render process separately but in the end, it should sit only
 /// in the main process & the render process should not have a resource loop but go
 /// through the main process to request for resource.
+pub mod archive;
+pub mod cache;
 pub mod error;
 pub mod request;
 pub mod resource_loop;
+pub mod response;
+mod scheduler;
 
 pub use resource_loop::*;
@@ -1,33 +1,181 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
-use super::error::LoadError;
+use super::{cache::CacheMode, error::LoadError, response::Response};
+use tokio::sync::Notify;
 use url::Url;
 
 pub type Bytes = Vec<u8>;
 
+/// What the requested resource is used for, which decides how soon it is fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
+    Document,
+    Style,
+    Image,
+    Other,
+}
+
+impl Destination {
+    /// Requests with a higher priority are started first. Documents come before
+    /// the stylesheets that block their rendering, and images come last.
+    pub fn priority(&self) -> u8 {
+        match self {
+            Destination::Document => 3,
+            Destination::Style => 2,
+            Destination::Other => 1,
+            Destination::Image => 0,
+        }
+    }
+}
+
+/// Cancels every request it is attached to. Queued requests are dropped and
+/// in-flight transfers are interrupted, their listener receives
+/// `LoadError::Aborted`.
+#[derive(Clone, Default)]
+pub struct AbortHandle {
+    aborted: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl AbortHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Resolve once `abort` has been called.
+    pub async fn aborted(&self) {
+        let notified = self.notify.notified();
+        if self.is_aborted() {
+            return;
+        }
+        notified.await;
+    }
+}
+
 pub trait FetchListener: Sync + Send {
     fn on_queued(&self) {}
     fn on_started(&self) {}
     #[allow(unused)]
-    fn on_finished(&self, bytes: Bytes) {}
+    fn on_finished(&self, response: Response) {}
     #[allow(unused)]
     fn on_errored(&self, error: LoadError) {}
 }
 
 pub struct LoadRequest {
     url: Url,
+    method: String,
+    headers: Vec<(String, String)>,
+    body: Option<Bytes>,
+    cache_mode: CacheMode,
+    destination: Destination,
+    abort_handle: Option<AbortHandle>,
     listener: Arc<dyn FetchListener>,
 }
 
 impl LoadRequest {
+    /// A `GET` request without any header. Use the `with_*` methods to customize it.
     pub fn new(url: Url, listener: Arc<dyn FetchListener>) -> Self {
-        Self { url, listener }
+        Self {
+            url,
+            method: "GET".to_string(),
+            headers: Vec::new(),
+            body: None,
+            cache_mode: CacheMode::Default,
+            destination: Destination::Other,
+            abort_handle: None,
+            listener,
+        }
+    }
+
+    pub fn with_method(mut self, method: &str) -> Self {
+        self.method = method.to_uppercase();
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: Bytes) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    pub fn with_cache_mode(mut self, cache_mode: CacheMode) -> Self {
+        self.cache_mode = cache_mode;
+        self
+    }
+
+    pub fn with_destination(mut self, destination: Destination) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    pub fn with_abort_handle(mut self, abort_handle: AbortHandle) -> Self {
+        self.abort_handle = Some(abort_handle);
+        self
+    }
+
+    /// The same request sent to another URL.
+    pub fn with_url(mut self, url: Url) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// The same request reporting to another listener.
+    pub(super) fn with_listener(mut self, listener: Arc<dyn FetchListener>) -> Self {
+        self.listener = listener;
+        self
     }
 
     pub fn url(&self) -> &Url {
         &self.url
     }
 
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    pub fn body(&self) -> Option<&Bytes> {
+        self.body.as_ref()
+    }
+
+    pub fn cache_mode(&self) -> CacheMode {
+        self.cache_mode
+    }
+
+    pub fn destination(&self) -> Destination {
+        self.destination
+    }
+
+    pub fn abort_handle(&self) -> Option<&AbortHandle> {
+        self.abort_handle.as_ref()
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.abort_handle
+            .as_ref()
+            .map(|handle| handle.is_aborted())
+            .unwrap_or(false)
+    }
+
     pub fn listener(&self) -> Arc<dyn FetchListener> {
         self.listener.clone()
     }
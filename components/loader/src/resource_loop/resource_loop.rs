@@ -1,129 +1,378 @@
-use std::collections::{HashMap, VecDeque};
+use super::{
+    archive::{ArchiveMode, NetworkArchive},
+    cache::{CacheMode, CachedResponse, HttpCache},
+    error::LoadError,
+    request::{FetchListener, LoadRequest},
+    response::Response,
+    scheduler::Scheduler,
+};
+use flume::{unbounded, Receiver, Sender};
+use lazy_static::lazy_static;
+use net::{
+    cookie::CookieJar,
+    http::{self, HttpRequest, HttpResponse},
+};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::runtime::{Builder, Runtime};
+use url::{data_url::DataUrl, parser::URLParser, Url};
 
-use super::{error::LoadError, request::LoadRequest};
-use flume::{bounded, select, unbounded, Receiver, Sender};
-use net::http::{self, HttpResponse};
-use url::{parser::URLParser, Url};
+/// Upper bound of the threads fetching resources, shared by all resource loops.
+const MAX_FETCH_THREADS: usize = 64;
+
+lazy_static! {
+    /// Runtime shared by all fetches so that connections can be reused. Fetches
+    /// run on its blocking pool, network transfers on its workers.
+    pub(super) static ref RUNTIME: Runtime = Builder::new_multi_thread()
+        .enable_all()
+        .max_blocking_threads(MAX_FETCH_THREADS)
+        .build()
+        .expect("Unable to start the network runtime");
+}
 
 pub struct ResourceLoop {
     request_channel: (Sender<LoadRequest>, Receiver<LoadRequest>),
+    deterministic: bool,
+    context: FetchContext,
+}
+
+/// State shared by every fetch of a resource loop.
+#[derive(Clone)]
+pub(super) struct FetchContext {
+    cache: Option<HttpCache>,
+    cookie_jar: CookieJar,
+    archive: Option<NetworkArchive>,
 }
 
 impl ResourceLoop {
+    /// A resource loop that schedules requests by priority. Responses are not
+    /// cached unless a cache is given with `with_cache`.
     pub fn new() -> Self {
         Self {
             request_channel: unbounded(),
+            deterministic: false,
+            context: FetchContext {
+                cache: None,
+                cookie_jar: CookieJar::new(),
+                archive: None,
+            },
+        }
+    }
+
+    /// A resource loop that fetches requests one at a time, in the order they were
+    /// received and regardless of their priority. This trades throughput for
+    /// reproducible loading order. Responses are never read from nor written to the HTTP cache.
+    pub fn new_deterministic() -> Self {
+        Self {
+            request_channel: unbounded(),
+            deterministic: true,
+            context: FetchContext {
+                cache: None,
+                cookie_jar: CookieJar::new(),
+                archive: None,
+            },
         }
     }
 
+    /// Use the given cache for remote resources, or disable caching with `None`.
+    pub fn with_cache(mut self, cache: Option<HttpCache>) -> Self {
+        self.context.cache = cache;
+        self
+    }
+
+    /// Share cookies with other resource loops using the same jar. Each loop
+    /// starts with an empty in-memory jar otherwise.
+    pub fn with_cookie_jar(mut self, cookie_jar: CookieJar) -> Self {
+        self.context.cookie_jar = cookie_jar;
+        self
+    }
+
+    /// Record remote responses to the archive, or serve them from it without
+    /// touching the network, depending on the mode of the archive.
+    pub fn with_archive(mut self, archive: Option<NetworkArchive>) -> Self {
+        self.context.archive = archive;
+        self
+    }
+
     fn sender(&self) -> Sender<LoadRequest> {
         let (sender, _) = self.request_channel.clone();
         sender
     }
 
     pub fn start_loop(&self) -> Sender<LoadRequest> {
-        let request_rx = self.request_channel.1.clone();
-        let tick_rx = tick(1);
-
-        let mut request_queue = VecDeque::new();
-        let mut host_request_counter: HashMap<Url, u64> = HashMap::new();
+        if self.deterministic {
+            return self.start_sequential_loop();
+        }
 
-        std::thread::spawn(move || loop {
-            enum Event {
-                Tick,
-                QueueRequest(LoadRequest),
-            }
-            let event = select::Selector::new()
-                .recv(&request_rx, |e| e.map(|req| Event::QueueRequest(req)))
-                .recv(&tick_rx, |e| e.map(|_| Event::Tick))
-                .wait();
+        let request_rx = self.request_channel.1.clone();
+        let scheduler = Scheduler::new(self.context.clone());
+        std::thread::spawn(move || scheduler.run(request_rx));
+        self.sender()
+    }
 
-            if event.is_err() {
-                break;
-            }
+    fn start_sequential_loop(&self) -> Sender<LoadRequest> {
+        let request_rx = self.request_channel.1.clone();
+        let context = self.context.clone();
 
-            match event.unwrap() {
-                Event::QueueRequest(request) => {
-                    request.listener().on_queued();
-                    request_queue.push_back(request);
-                }
-                Event::Tick => {
-                    if request_queue.is_empty() {
-                        continue;
-                    }
-                    let request = request_queue.pop_front().unwrap();
-
-                    let url = request.url();
-                    let request_count = host_request_counter.get(&url).unwrap_or(&0) + 1;
-                    host_request_counter.insert(url.clone(), request_count);
-
-                    request.listener().on_started();
-                    fetch_async(request);
+        std::thread::spawn(move || {
+            for request in request_rx.iter() {
+                request.listener().on_queued();
+                if request.is_aborted() {
+                    request.listener().on_errored(LoadError::Aborted);
+                    continue;
                 }
+                request.listener().on_started();
+                fetch_guarded(request, &context);
             }
         });
         self.sender()
     }
 }
 
-fn tick(ms: u64) -> Receiver<()> {
-    let (tx, rx) = bounded(1);
-    let _ = std::thread::spawn(move || loop {
-        if tx.send(()).is_err() {
-            break;
-        }
-        std::thread::sleep(std::time::Duration::from_millis(ms));
+/// Fetch the request, reporting an error to its listener if the fetch panics
+/// as the listener would otherwise never hear back from it. A listener that
+/// already got its response or error is not told again.
+pub(super) fn fetch_guarded(request: LoadRequest, context: &FetchContext) {
+    let listener = Arc::new(SettleOnce {
+        inner: request.listener(),
+        settled: AtomicBool::new(false),
     });
-    rx
+    let url = request.url().clone();
+    let request = request.with_listener(listener.clone());
+
+    if panic::catch_unwind(AssertUnwindSafe(|| fetch(request, context))).is_err() {
+        listener.on_errored(LoadError::IOError(format!(
+            "Loading {} failed unexpectedly",
+            url
+        )));
+    }
 }
 
-/// Spawn a new thread & fetch the request. Return resource bytes (Vec<u8>) if success, otherwise a LoadError.
-fn fetch_async(request: LoadRequest) {
-    std::thread::spawn(|| fetch(request));
+/// Forwards at most one of `on_finished` and `on_errored` to the listener.
+struct SettleOnce {
+    inner: Arc<dyn FetchListener>,
+    settled: AtomicBool,
 }
 
-fn fetch(request: LoadRequest) {
+impl FetchListener for SettleOnce {
+    fn on_queued(&self) {
+        self.inner.on_queued();
+    }
+
+    fn on_started(&self) {
+        self.inner.on_started();
+    }
+
+    fn on_finished(&self, response: Response) {
+        if !self.settled.swap(true, Ordering::SeqCst) {
+            self.inner.on_finished(response);
+        }
+    }
+
+    fn on_errored(&self, error: LoadError) {
+        if !self.settled.swap(true, Ordering::SeqCst) {
+            self.inner.on_errored(error);
+        }
+    }
+}
+
+fn fetch(request: LoadRequest, context: &FetchContext) {
     match request.url().scheme.as_str() {
         "file" => fetch_local(request),
-        "http" | "https" => fetch_remote(request),
-        "view-source" => fetch_source(request),
-        scheme => request
-            .listener()
-            .on_errored(LoadError::UnsupportedProtocol(scheme.to_string())),
+        "http" | "https" => fetch_remote(request, context),
+        "view-source" => fetch_source(request, context),
+        "data" => fetch_data(request),
+        scheme => {
+            let error = LoadError::UnsupportedProtocol(scheme.to_string());
+            deliver(&request, Err(error))
+        }
     }
 }
 
-fn fetch_source(request: LoadRequest) {
+fn fetch_source(request: LoadRequest, context: &FetchContext) {
     let target_url = URLParser::parse(&request.url().path.as_str(), None)
         .ok_or_else(|| LoadError::InvalidURL(request.url().as_str()));
 
-    let listener = request.listener();
     match target_url {
-        Ok(url) => fetch(LoadRequest::new(url, listener)),
-        Err(error) => listener.on_errored(error),
+        Ok(url) => fetch(request.with_url(url), context),
+        Err(error) => deliver(&request, Err(error)),
     }
 }
 
+fn fetch_data(request: LoadRequest) {
+    let response = DataUrl::parse(request.url())
+        .ok_or_else(|| LoadError::InvalidURL(request.url().as_str()))
+        .map(|data_url| {
+            let mut response = Response::ok(request.url().clone(), data_url.body.clone());
+            response
+                .headers
+                .push(("Content-Type".to_string(), data_url.content_type()));
+            response
+        });
+    deliver(&request, response);
+}
+
 fn fetch_local(request: LoadRequest) {
     let fetch_result =
         std::fs::read(request.url().path.as_str()).map_err(|e| LoadError::IOError(e.to_string()));
 
-    let listener = request.listener();
-    match fetch_result {
-        Ok(bytes) => listener.on_finished(bytes),
-        Err(error) => listener.on_errored(error),
-    }
+    let response = fetch_result.map(|bytes| Response::ok(request.url().clone(), bytes));
+    deliver(&request, response);
 }
 
-fn fetch_remote(request: LoadRequest) {
-    let rt = tokio::runtime::Runtime::new().unwrap();
-    let fetch_result = match rt.block_on(http::request("GET", &request.url().as_str())) {
-        HttpResponse::Success(bytes) => Ok(bytes),
-        HttpResponse::Failure(err) => Err(LoadError::IOError(err)),
+fn fetch_remote(request: LoadRequest, context: &FetchContext) {
+    let url = request.url().as_str();
+
+    if let Some(archive) = context.archive.as_ref() {
+        if archive.mode() == ArchiveMode::Replay {
+            let response = archive
+                .get(request.method(), request.url())
+                .ok_or_else(|| LoadError::IOError(format!("{} is not in the archive", url)));
+            deliver(&request, response);
+            return;
+        }
+    }
+
+    // Only GET responses are cached, other methods always go to the network.
+    let cache = context.cache.as_ref().filter(|_| request.method() == "GET");
+    let cached = cache
+        .filter(|_| request.cache_mode() != CacheMode::Reload)
+        .and_then(|cache| cache.get(&url));
+
+    if let Some(cached) = &cached {
+        if request.cache_mode() == CacheMode::Default && cached.is_fresh() {
+            log::debug!("Loaded {} from cache", url);
+            deliver_remote(&request, context, cached_response(cached, request.url()));
+            return;
+        }
+    }
+
+    let mut headers = request.headers().to_vec();
+    if let Some(cached) = &cached {
+        headers.extend(cached.validators());
+    }
+
+    let http_request = HttpRequest {
+        method: request.method().to_string(),
+        url: url.clone(),
+        headers,
+        body: request.body().cloned(),
     };
+    let abort_handle = request.abort_handle().cloned().unwrap_or_default();
+    let fetch_result = RUNTIME.block_on(async {
+        tokio::select! {
+            result = http::request(&http_request, Some(&context.cookie_jar)) => {
+                result.map_err(LoadError::IOError)
+            }
+            _ = abort_handle.aborted() => Err(LoadError::Aborted),
+        }
+    });
+
+    match (fetch_result, cache, cached) {
+        (Ok(response), Some(cache), Some(cached)) if response.status == 304 => {
+            log::debug!("Revalidated {} from cache", url);
+            let cached = cache.update(&cached, &response.headers);
+            deliver_remote(&request, context, cached_response(&cached, request.url()));
+        }
+        (Ok(response), cache, _) => {
+            // Redirected responses are not cached as the entry would lose its final URL.
+            if let Some(cache) = cache.filter(|_| response.url == url) {
+                cache.put(
+                    &url,
+                    response.status,
+                    &response.status_text,
+                    &response.headers,
+                    &response.body,
+                );
+            }
+            deliver_remote(&request, context, remote_response(response, request.url()));
+        }
+        (Err(error), ..) => deliver(&request, Err(error)),
+    }
+}
+
+/// Hand the result to the listener of the request, unless the request was
+/// aborted in the meantime: responses of aborted requests are stale and are
+/// reported as `LoadError::Aborted` instead.
+fn deliver(request: &LoadRequest, result: Result<Response, LoadError>) {
     let listener = request.listener();
-    match fetch_result {
-        Ok(bytes) => listener.on_finished(bytes),
+    match result {
+        _ if request.is_aborted() => listener.on_errored(LoadError::Aborted),
+        Ok(response) => listener.on_finished(response),
         Err(error) => listener.on_errored(error),
     }
 }
+
+/// Deliver a remote response, recording it first if the loop is recording.
+fn deliver_remote(request: &LoadRequest, context: &FetchContext, response: Response) {
+    if let Some(archive) = &context.archive {
+        archive.store(request.method(), request.url(), &response);
+    }
+    deliver(request, Ok(response));
+}
+
+fn remote_response(response: HttpResponse, request_url: &Url) -> Response {
+    Response {
+        url: URLParser::parse(&response.url, None).unwrap_or_else(|| request_url.clone()),
+        status: response.status,
+        status_text: response.status_text,
+        headers: response.headers,
+        body: response.body,
+    }
+}
+
+fn cached_response(cached: &CachedResponse, request_url: &Url) -> Response {
+    Response {
+        url: request_url.clone(),
+        status: cached.status,
+        status_text: cached.status_text.clone(),
+        headers: cached.headers.clone(),
+        body: cached.body.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    /// Counts its terminal callbacks, then panics while handling the response.
+    #[derive(Default)]
+    struct PanickingListener {
+        finished: AtomicUsize,
+        errored: AtomicUsize,
+    }
+
+    impl FetchListener for PanickingListener {
+        fn on_finished(&self, _: Response) {
+            self.finished.fetch_add(1, Ordering::SeqCst);
+            panic!("listener failed");
+        }
+
+        fn on_errored(&self, _: LoadError) {
+            self.errored.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn settle_once_when_listener_panics() {
+        let listener = Arc::new(PanickingListener::default());
+        let url = URLParser::parse("data:text/plain,moon", None).unwrap();
+        let context = FetchContext {
+            cache: None,
+            cookie_jar: CookieJar::new(),
+            archive: None,
+        };
+
+        fetch_guarded(LoadRequest::new(url, listener.clone()), &context);
+
+        assert_eq!(listener.finished.load(Ordering::SeqCst), 1);
+        assert_eq!(listener.errored.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,82 @@
+use net::http::find_header;
+use url::Url;
+
+use super::request::Bytes;
+
+/// A fetched resource together with the metadata its protocol provides. Local
+/// files are reported as successful responses without headers.
+pub struct Response {
+    /// The URL of the resource, after following redirects.
+    pub url: Url,
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Bytes,
+}
+
+impl Response {
+    pub fn ok(url: Url, body: Bytes) -> Self {
+        Self {
+            url,
+            status: 200,
+            status_text: "OK".to_string(),
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    /// The MIME type of the `Content-Type` header, without its parameters.
+    pub fn mime_type(&self) -> Option<String> {
+        self.header("content-type")
+            .and_then(|value| value.split(';').next())
+            .map(|mime| mime.trim().to_lowercase())
+    }
+
+    /// The `charset` parameter of the `Content-Type` header.
+    pub fn charset(&self) -> Option<String> {
+        self.header("content-type")?
+            .split(';')
+            .skip(1)
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+            .map(|(_, value)| value.trim().trim_matches('"').to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::parser::URLParser;
+
+    use super::*;
+
+    fn response_with_content_type(content_type: &str) -> Response {
+        let url = URLParser::parse("https://example.com/", None).unwrap();
+        let mut response = Response::ok(url, Vec::new());
+        response
+            .headers
+            .push(("Content-Type".to_string(), content_type.to_string()));
+        response
+    }
+
+    #[test]
+    fn parse_content_type() {
+        let response = response_with_content_type("text/HTML; charset=\"ISO-8859-1\"");
+        assert_eq!(response.mime_type(), Some("text/html".to_string()));
+        assert_eq!(response.charset(), Some("ISO-8859-1".to_string()));
+    }
+
+    #[test]
+    fn parse_content_type_without_charset() {
+        let response = response_with_content_type("text/css");
+        assert_eq!(response.mime_type(), Some("text/css".to_string()));
+        assert_eq!(response.charset(), None);
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::{HashMap, VecDeque};
+
+use flume::{unbounded, Receiver, Selector, Sender};
+use url::{Host, Url};
+
+use super::{
+    error::LoadError,
+    request::LoadRequest,
+    resource_loop::{fetch_guarded, FetchContext, RUNTIME},
+};
+
+/// How many requests to the same origin can be in flight at once.
+const MAX_CONNECTIONS_PER_HOST: usize = 6;
+/// How many requests a resource loop can have in flight at once.
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+
+/// Starts queued requests by priority, as long as neither the loop nor the
+/// host of the request has reached its concurrency limit. Requests of the same
+/// priority are started in the order they were queued.
+pub(super) struct Scheduler {
+    context: FetchContext,
+    queue: RequestQueue,
+    finished_channel: (Sender<String>, Receiver<String>),
+}
+
+/// The requests waiting to be started and the number of requests in flight,
+/// overall and per host.
+struct RequestQueue {
+    pending: VecDeque<LoadRequest>,
+    in_flight: HashMap<String, usize>,
+    in_flight_count: usize,
+}
+
+/// Releases the slot of a started request once it is dropped, even if the
+/// fetch panicked, so that its host doesn't stay blocked forever.
+struct InFlightSlot {
+    host: String,
+    finished_tx: Sender<String>,
+}
+
+impl Scheduler {
+    pub fn new(context: FetchContext) -> Self {
+        Self {
+            context,
+            queue: RequestQueue::new(),
+            finished_channel: unbounded(),
+        }
+    }
+
+    /// Schedule requests until every sender of `request_rx` is dropped.
+    pub fn run(mut self, request_rx: Receiver<LoadRequest>) {
+        let finished_rx = self.finished_channel.1.clone();
+
+        enum Event {
+            QueueRequest(Box<LoadRequest>),
+            Finished(String),
+        }
+
+        loop {
+            let event = Selector::new()
+                .recv(&request_rx, |e| {
+                    e.map(|req| Event::QueueRequest(Box::new(req)))
+                })
+                .recv(&finished_rx, |e| e.map(Event::Finished))
+                .wait();
+
+            match event {
+                Ok(Event::QueueRequest(request)) => {
+                    request.listener().on_queued();
+                    self.queue.push(*request);
+                }
+                Ok(Event::Finished(host)) => self.queue.finish(&host),
+                Err(_) => break,
+            }
+
+            self.dispatch();
+        }
+    }
+
+    fn dispatch(&mut self) {
+        self.queue.drop_aborted_requests();
+
+        while let Some(request) = self.queue.next() {
+            self.start(request);
+        }
+    }
+
+    fn start(&self, request: LoadRequest) {
+        let slot = InFlightSlot {
+            host: host_key(request.url()),
+            finished_tx: self.finished_channel.0.clone(),
+        };
+
+        request.listener().on_started();
+
+        let context = self.context.clone();
+        RUNTIME.spawn_blocking(move || {
+            let _slot = slot;
+            fetch_guarded(request, &context);
+        });
+    }
+}
+
+impl RequestQueue {
+    fn new() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            in_flight: HashMap::new(),
+            in_flight_count: 0,
+        }
+    }
+
+    fn push(&mut self, request: LoadRequest) {
+        self.pending.push_back(request);
+    }
+
+    /// Take the request to start next and count it as in flight, or `None` if
+    /// every pending request is held back by a concurrency limit.
+    fn next(&mut self) -> Option<LoadRequest> {
+        if self.in_flight_count >= MAX_CONCURRENT_REQUESTS {
+            return None;
+        }
+
+        let index = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, request)| self.host_in_flight(request.url()) < MAX_CONNECTIONS_PER_HOST)
+            .max_by_key(|(index, request)| {
+                (request.destination().priority(), std::cmp::Reverse(*index))
+            })
+            .map(|(index, _)| index)?;
+
+        let request = self.pending.remove(index)?;
+        *self.in_flight.entry(host_key(request.url())).or_default() += 1;
+        self.in_flight_count += 1;
+        Some(request)
+    }
+
+    fn drop_aborted_requests(&mut self) {
+        self.pending.retain(|request| {
+            if request.is_aborted() {
+                request.listener().on_errored(LoadError::Aborted);
+                return false;
+            }
+            true
+        });
+    }
+
+    fn finish(&mut self, host: &str) {
+        self.in_flight_count -= 1;
+        if let Some(count) = self.in_flight.get_mut(host) {
+            *count -= 1;
+            if *count == 0 {
+                self.in_flight.remove(host);
+            }
+        }
+    }
+
+    fn host_in_flight(&self, url: &Url) -> usize {
+        self.in_flight
+            .get(&host_key(url))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for InFlightSlot {
+    fn drop(&mut self) {
+        let _ = self.finished_tx.send(std::mem::take(&mut self.host));
+    }
+}
+
+/// Requests are limited per scheme, host & port. Local files share one limit.
+fn host_key(url: &Url) -> String {
+    format!(
+        "{}://{}:{}",
+        url.scheme,
+        url.host.as_ref().map(Host::as_str).unwrap_or_default(),
+        url.port.map(|port| port.to_string()).unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use url::parser::URLParser;
+
+    use super::*;
+    use crate::resource_loop::request::{AbortHandle, Destination, FetchListener};
+
+    #[derive(Default)]
+    struct ErrorListener {
+        errors: Mutex<Vec<String>>,
+    }
+
+    impl FetchListener for ErrorListener {
+        fn on_errored(&self, error: LoadError) {
+            self.errors.lock().unwrap().push(error.to_string());
+        }
+    }
+
+    fn request(url: &str, destination: Destination) -> LoadRequest {
+        let url = URLParser::parse(url, None).unwrap();
+        LoadRequest::new(url, Arc::new(ErrorListener::default())).with_destination(destination)
+    }
+
+    fn started_urls(queue: &mut RequestQueue) -> Vec<String> {
+        std::iter::from_fn(|| queue.next())
+            .map(|request| request.url().as_str())
+            .collect()
+    }
+
+    #[test]
+    fn start_by_priority_then_in_queue_order() {
+        let mut queue = RequestQueue::new();
+        queue.push(request("https://a.com/other-1", Destination::Other));
+        queue.push(request("https://a.com/style-1", Destination::Style));
+        queue.push(request("https://b.com/other-2", Destination::Other));
+        queue.push(request("https://b.com/document", Destination::Document));
+        queue.push(request("https://c.com/style-2", Destination::Style));
+
+        assert_eq!(
+            started_urls(&mut queue),
+            vec![
+                "https://b.com/document",
+                "https://a.com/style-1",
+                "https://c.com/style-2",
+                "https://a.com/other-1",
+                "https://b.com/other-2",
+            ]
+        );
+    }
+
+    #[test]
+    fn limit_requests_per_host() {
+        let mut queue = RequestQueue::new();
+        for index in 0..8 {
+            queue.push(request(
+                &format!("https://a.com/{}", index),
+                Destination::Other,
+            ));
+        }
+        queue.push(request("https://a.com:8080/", Destination::Other));
+
+        let started = started_urls(&mut queue);
+        assert_eq!(started.len(), 7);
+        assert_eq!(started.last().unwrap(), "https://a.com:8080/");
+        assert!(queue.next().is_none());
+
+        queue.finish("https://a.com:");
+        assert_eq!(started_urls(&mut queue), vec!["https://a.com/6"]);
+    }
+
+    #[test]
+    fn limit_concurrent_requests() {
+        let mut queue = RequestQueue::new();
+        for index in 0..20 {
+            queue.push(request(
+                &format!("https://host-{}.com/", index),
+                Destination::Other,
+            ));
+        }
+
+        assert_eq!(started_urls(&mut queue).len(), MAX_CONCURRENT_REQUESTS);
+
+        queue.finish("https://host-0.com:");
+        queue.finish("https://host-1.com:");
+        assert_eq!(
+            started_urls(&mut queue),
+            vec!["https://host-16.com/", "https://host-17.com/"]
+        );
+    }
+
+    #[test]
+    fn drop_aborted_requests() {
+        let listener = Arc::new(ErrorListener::default());
+        let abort_handle = AbortHandle::new();
+        let url = URLParser::parse("https://a.com/aborted", None).unwrap();
+
+        let mut queue = RequestQueue::new();
+        queue.push(LoadRequest::new(url, listener.clone()).with_abort_handle(abort_handle.clone()));
+        queue.push(request("https://a.com/kept", Destination::Other));
+
+        abort_handle.abort();
+        queue.drop_aborted_requests();
+
+        assert_eq!(
+            *listener.errors.lock().unwrap(),
+            vec![LoadError::Aborted.to_string()]
+        );
+        assert_eq!(started_urls(&mut queue), vec!["https://a.com/kept"]);
+    }
+
+    #[test]
+    fn release_slot_when_fetch_panics() {
+        let (finished_tx, finished_rx) = unbounded();
+        let slot = InFlightSlot {
+            host: "https://a.com:".to_string(),
+            finished_tx,
+        };
+
+        let result = std::thread::spawn(move || {
+            let _slot = slot;
+            panic!("fetch failed");
+        })
+        .join();
+
+        assert!(result.is_err());
+        assert_eq!(finished_rx.try_recv().unwrap(), "https://a.com:");
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use url::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    /// Cookies without a `Domain` attribute are only sent back to the exact host
+    /// that set them.
+    pub host_only: bool,
+    pub path: String,
+    /// `None` for session cookies, which are never written to disk.
+    pub expires: Option<SystemTime>,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: SameSite,
+}
+
+impl Cookie {
+    /// Parse a `Set-Cookie` header received from `url`, following RFC 6265.
+    /// Returns `None` if the cookie is malformed or not allowed for that URL.
+    pub fn parse(header: &str, url: &Url) -> Option<Cookie> {
+        let host = url.host.as_ref()?.to_string();
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url),
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: SameSite::Lax,
+        };
+
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, value) = match attribute.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (attribute.trim(), ""),
+            };
+
+            match key.to_lowercase().as_str() {
+                "domain" => {
+                    let domain = value.trim_start_matches('.').to_lowercase();
+                    if domain.is_empty() {
+                        continue;
+                    }
+                    if !domain_match(&host, &domain) || (!domain.contains('.') && domain != host) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "expires" => {
+                    if let Some(expires) = parse_cookie_date(value) {
+                        cookie.expires = Some(expires);
+                    }
+                }
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" => {
+                    cookie.same_site = match value.to_lowercase().as_str() {
+                        "strict" => SameSite::Strict,
+                        "none" => SameSite::None,
+                        _ => SameSite::Lax,
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Max-Age takes precedence over Expires, and a non-positive value expires
+        // the cookie right away.
+        if let Some(max_age) = max_age {
+            cookie.expires = Some(if max_age <= 0 {
+                UNIX_EPOCH
+            } else {
+                SystemTime::now() + Duration::from_secs(max_age as u64)
+            });
+        }
+
+        if cookie.secure && url.scheme != "https" {
+            return None;
+        }
+
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires
+            .map(|expires| expires <= SystemTime::now())
+            .unwrap_or(false)
+    }
+
+    /// Whether this cookie should be attached to a request for `url`.
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = match &url.host {
+            Some(host) => host.to_string(),
+            None => return false,
+        };
+
+        let domain_matches = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(&host, &self.domain)
+        };
+
+        domain_matches
+            && path_match(&request_path(url), &self.path)
+            && (!self.secure || url.scheme == "https")
+            && !self.is_expired()
+    }
+
+    fn is_same_cookie(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+/// Cookies received from every HTTP response, shared by all the clones of the
+/// jar. Jars opened from a file write persistent cookies back to it whenever
+/// they change.
+///
+/// `SameSite` and `HttpOnly` are recorded but not enforced: there are no scripts
+/// to hide cookies from and every request is treated as same-site.
+#[derive(Clone, Default)]
+pub struct CookieJar {
+    cookies: Arc<Mutex<Vec<Cookie>>>,
+    path: Option<PathBuf>,
+}
+
+impl CookieJar {
+    /// A jar that only lives in memory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A jar persisted to `path`, starting with the cookies already stored there.
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let cookies = match std::fs::read_to_string(&path) {
+            Ok(content) => content.lines().filter_map(deserialize_cookie).collect(),
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            cookies: Arc::new(Mutex::new(cookies)),
+            path: Some(path),
+        }
+    }
+
+    /// The cookie file in the profile directory of the current user.
+    pub fn default_location() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("moon")
+            .join("cookies.txt")
+    }
+
+    /// Store the cookies of every `Set-Cookie` header received from `url`.
+    pub fn store_response_cookies(&self, url: &Url, headers: &[(String, String)]) {
+        let received: Vec<Cookie> = headers
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"))
+            .filter_map(|(_, value)| Cookie::parse(value, url))
+            .collect();
+
+        if received.is_empty() {
+            return;
+        }
+
+        let mut cookies = self.cookies.lock().unwrap();
+        for cookie in received {
+            cookies.retain(|stored| !stored.is_same_cookie(&cookie));
+            if !cookie.is_expired() {
+                cookies.push(cookie);
+            }
+        }
+        self.save(&cookies);
+    }
+
+    /// The value of the `Cookie` header for a request to `url`. Cookies with
+    /// longer paths are listed first.
+    pub fn cookie_header(&self, url: &Url) -> Option<String> {
+        let cookies = self.cookies.lock().unwrap();
+        let mut matching: Vec<&Cookie> = cookies.iter().filter(|c| c.matches(url)).collect();
+        if matching.is_empty() {
+            return None;
+        }
+
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        let pairs: Vec<String> = matching
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect();
+        Some(pairs.join("; "))
+    }
+
+    pub fn cookies(&self) -> Vec<Cookie> {
+        self.cookies.lock().unwrap().clone()
+    }
+
+    fn save(&self, cookies: &[Cookie]) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let content: String = cookies
+            .iter()
+            .filter(|cookie| cookie.expires.is_some() && !cookie.is_expired())
+            .map(serialize_cookie)
+            .collect();
+
+        let tmp_path = path.with_extension("tmp");
+        let result = path
+            .parent()
+            .map(std::fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| std::fs::write(&tmp_path, content))
+            .and_then(|_| std::fs::rename(&tmp_path, path));
+
+        if let Err(e) = result {
+            log::warn!("Unable to save cookies to {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// One cookie per line, with tab separated fields in the order of the struct.
+fn serialize_cookie(cookie: &Cookie) -> String {
+    let expires = cookie
+        .expires
+        .and_then(|expires| expires.duration_since(UNIX_EPOCH).ok())
+        .map(|expires| expires.as_secs())
+        .unwrap_or_default();
+    let same_site = match cookie.same_site {
+        SameSite::Strict => "Strict",
+        SameSite::Lax => "Lax",
+        SameSite::None => "None",
+    };
+
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+        cookie.name,
+        cookie.value,
+        cookie.domain,
+        cookie.host_only,
+        cookie.path,
+        expires,
+        cookie.secure,
+        cookie.http_only,
+        same_site
+    )
+}
+
+fn deserialize_cookie(line: &str) -> Option<Cookie> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() != 9 {
+        return None;
+    }
+
+    let cookie = Cookie {
+        name: fields[0].to_string(),
+        value: fields[1].to_string(),
+        domain: fields[2].to_string(),
+        host_only: fields[3].parse().ok()?,
+        path: fields[4].to_string(),
+        expires: Some(UNIX_EPOCH + Duration::from_secs(fields[5].parse().ok()?)),
+        secure: fields[6].parse().ok()?,
+        http_only: fields[7].parse().ok()?,
+        same_site: match fields[8] {
+            "Strict" => SameSite::Strict,
+            "None" => SameSite::None,
+            _ => SameSite::Lax,
+        },
+    };
+
+    Some(cookie).filter(|cookie| !cookie.is_expired())
+}
+
+/// Cookie dates come in many formats, most of which are HTTP dates that may
+/// use dashes instead of spaces between the day, month and year.
+fn parse_cookie_date(value: &str) -> Option<SystemTime> {
+    httpdate::parse_http_date(value)
+        .or_else(|_| httpdate::parse_http_date(&value.replace('-', " ")))
+        .ok()
+}
+
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host[..host.len() - domain.len()].ends_with('.')
+            && host.parse::<std::net::IpAddr>().is_err())
+}
+
+fn path_match(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+fn request_path(url: &Url) -> String {
+    format!("/{}", url.path.as_str().trim_start_matches('/'))
+}
+
+/// The directory of the request path, used when `Set-Cookie` has no `Path`.
+fn default_path(url: &Url) -> String {
+    let path = request_path(url);
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => path[..index].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::parser::URLParser;
+
+    use super::*;
+
+    fn url(input: &str) -> Url {
+        URLParser::parse(input, None).unwrap()
+    }
+
+    fn set_cookie(jar: &CookieJar, from: &str, header: &str) {
+        jar.store_response_cookies(
+            &url(from),
+            &[("Set-Cookie".to_string(), header.to_string())],
+        );
+    }
+
+    #[test]
+    fn parse_set_cookie_attributes() {
+        let cookie = Cookie::parse(
+            "id=a3fWa; Domain=.example.com; Path=/docs; Secure; HttpOnly; SameSite=Strict",
+            &url("https://www.example.com/login"),
+        )
+        .unwrap();
+
+        assert_eq!(cookie.name, "id");
+        assert_eq!(cookie.value, "a3fWa");
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only);
+        assert_eq!(cookie.path, "/docs");
+        assert!(cookie.secure && cookie.http_only);
+        assert_eq!(cookie.same_site, SameSite::Strict);
+    }
+
+    #[test]
+    fn reject_cookie_for_foreign_domain() {
+        assert!(Cookie::parse("id=1; Domain=other.com", &url("http://example.com/")).is_none());
+        assert!(Cookie::parse("id=1; Secure", &url("http://example.com/")).is_none());
+    }
+
+    #[test]
+    fn attach_matching_cookies() {
+        let jar = CookieJar::new();
+        set_cookie(&jar, "http://example.com/account/login", "session=1");
+        set_cookie(
+            &jar,
+            "http://example.com/",
+            "theme=dark; Domain=example.com",
+        );
+
+        assert_eq!(
+            jar.cookie_header(&url("http://example.com/account/settings")),
+            Some("session=1; theme=dark".to_string())
+        );
+        assert_eq!(
+            jar.cookie_header(&url("http://www.example.com/")),
+            Some("theme=dark".to_string())
+        );
+        assert_eq!(jar.cookie_header(&url("http://example.org/")), None);
+    }
+
+    #[test]
+    fn expire_cookies() {
+        let jar = CookieJar::new();
+        set_cookie(&jar, "http://example.com/", "session=1");
+        set_cookie(&jar, "http://example.com/", "session=1; Max-Age=0");
+        set_cookie(
+            &jar,
+            "http://example.com/",
+            "old=1; Expires=Wed, 21-Oct-2015 07:28:00 GMT",
+        );
+
+        assert_eq!(jar.cookie_header(&url("http://example.com/")), None);
+    }
+
+    #[test]
+    fn persist_cookies() {
+        let path = std::env::temp_dir().join("moon-cookie-test.txt");
+        let _ = std::fs::remove_file(&path);
+
+        let jar = CookieJar::open(&path);
+        set_cookie(&jar, "http://example.com/", "session=1");
+        set_cookie(&jar, "http://example.com/", "id=2; Max-Age=3600");
+
+        let reopened = CookieJar::open(&path);
+        assert_eq!(
+            reopened.cookie_header(&url("http://example.com/")),
+            Some("id=2".to_string())
+        );
+    }
+}
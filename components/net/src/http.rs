@@ -1,35 +1,281 @@
-use reqwest::{Client, Method};
+use std::sync::{OnceLock, RwLock};
 
-pub enum HttpResponse {
-    Success(Vec<u8>),
-    Failure(String),
+use reqwest::{redirect::Policy, Client, Method, StatusCode};
+use url::parser::URLParser;
+
+use crate::cookie::CookieJar;
+
+pub const DEFAULT_USER_AGENT: &str = concat!("Moon/", env!("CARGO_PKG_VERSION"));
+pub const DEFAULT_ACCEPT: &str = "*/*";
+const MAX_REDIRECTS: usize = 20;
+/// Headers describing the body, which are dropped along with it when a redirect
+/// turns the request into a GET.
+const REQUEST_BODY_HEADERS: [&str; 5] = [
+    "content-encoding",
+    "content-language",
+    "content-length",
+    "content-location",
+    "content-type",
+];
+/// Credentials of the caller, which must not leak to another origin on redirect.
+const CREDENTIAL_HEADERS: [&str; 2] = ["authorization", "cookie"];
+
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    /// The URL of the response, after following redirects.
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn get(url: &str) -> Self {
+        Self {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
 }
 
-static mut HTTP_CLIENT: Option<Client> = None;
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("content-type")
+    }
+}
+
+static HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
+static USER_AGENT: RwLock<Option<String>> = RwLock::new(None);
 
 fn get_http_client() -> &'static Client {
-    unsafe {
-        if HTTP_CLIENT.is_none() {
-            HTTP_CLIENT = Some(Client::new());
+    HTTP_CLIENT.get_or_init(|| {
+        // Redirects are followed by `request` so that cookies are stored and
+        // sent at every hop.
+        Client::builder()
+            .redirect(Policy::none())
+            .build()
+            .expect("Unable to build http client")
+    })
+}
+
+/// Override the `User-Agent` header sent with requests that don't set their own.
+pub fn set_user_agent(user_agent: &str) {
+    *USER_AGENT.write().unwrap() = Some(user_agent.to_string());
+}
+
+pub fn user_agent() -> String {
+    USER_AGENT
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string())
+}
+
+/// Send a request and read the whole response, following redirects. Responses
+/// with an error status are returned as well, only transport failures result in
+/// an error. Cookies from `cookie_jar` are attached to every request and the
+/// ones set by every response are stored in it.
+pub async fn request(
+    request: &HttpRequest,
+    cookie_jar: Option<&CookieJar>,
+) -> Result<HttpResponse, String> {
+    let mut method = Method::from_bytes(request.method.to_uppercase().as_bytes())
+        .map_err(|_| format!("Unsupported method: {}", request.method))?;
+    let mut url = request.url.clone();
+    let mut headers = request.headers.clone();
+    let mut body = request.body.clone();
+
+    for _ in 0..=MAX_REDIRECTS {
+        let response = send(method.clone(), &url, &headers, body.clone(), cookie_jar).await?;
+        let status = response.status();
+
+        let location = response
+            .headers()
+            .get("location")
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| response.url().join(location).ok());
+
+        let location = match location {
+            Some(location) if status.is_redirection() => location,
+            _ => return read_response(response).await,
+        };
+
+        // Except for 307 & 308, redirects turn POST requests into GET requests.
+        if status == StatusCode::SEE_OTHER
+            || (method == Method::POST
+                && (status == StatusCode::MOVED_PERMANENTLY || status == StatusCode::FOUND))
+        {
+            if method != Method::HEAD {
+                method = Method::GET;
+            }
+            body = None;
+            remove_headers(&mut headers, &REQUEST_BODY_HEADERS);
         }
-        HTTP_CLIENT.as_ref().expect("Unable to obtain http client")
+        if location.origin() != response.url().origin() {
+            remove_headers(&mut headers, &CREDENTIAL_HEADERS);
+        }
+        url = location.to_string();
     }
+
+    Err(format!("Too many redirects while loading {}", request.url))
 }
 
-pub async fn request(method: &str, url: &str) -> HttpResponse {
+async fn send(
+    method: Method,
+    url: &str,
+    headers: &[(String, String)],
+    body: Option<Vec<u8>>,
+    cookie_jar: Option<&CookieJar>,
+) -> Result<reqwest::Response, String> {
     let client = get_http_client();
-    let method = match method.to_lowercase().as_str() {
-        "get" => Method::GET,
-        _ => return HttpResponse::Failure(format!("Unsupported method: {}", method)),
-    };
-
-    let response = match client.request(method, url).send().await {
-        Ok(response) => match response.bytes().await {
-            Ok(bytes) => HttpResponse::Success(bytes.to_vec()),
-            Err(e) => HttpResponse::Failure(e.to_string()),
-        },
-        Err(e) => HttpResponse::Failure(e.to_string()),
-    };
+    let mut builder = client.request(method, url);
+
+    let cookie_url = cookie_jar.and_then(|_| URLParser::parse(url, None));
+    let jar_cookies = cookie_jar
+        .zip(cookie_url.as_ref())
+        .and_then(|(jar, cookie_url)| jar.cookie_header(cookie_url));
 
+    for (name, value) in headers {
+        if !name.eq_ignore_ascii_case("cookie") {
+            builder = builder.header(name, value);
+        }
+    }
+    if find_header(headers, "user-agent").is_none() {
+        builder = builder.header("User-Agent", user_agent());
+    }
+    if find_header(headers, "accept").is_none() {
+        builder = builder.header("Accept", DEFAULT_ACCEPT);
+    }
+    if let Some(cookies) = merge_cookies(find_header(headers, "cookie"), jar_cookies) {
+        builder = builder.header("Cookie", cookies);
+    }
+    if let Some(body) = body {
+        builder = builder.body(body);
+    }
+
+    let response = builder.send().await.map_err(|e| e.to_string())?;
+
+    if let (Some(jar), Some(cookie_url)) = (cookie_jar, &cookie_url) {
+        jar.store_response_cookies(cookie_url, &read_headers(&response));
+    }
+
+    Ok(response)
+}
+
+/// Send the cookies given by the caller and the ones from the jar in a single
+/// `Cookie` header, as servers may only read one.
+fn merge_cookies(request_cookies: Option<&str>, jar_cookies: Option<String>) -> Option<String> {
+    match (request_cookies, jar_cookies) {
+        (Some(request_cookies), Some(jar_cookies)) => {
+            Some(format!("{}; {}", request_cookies, jar_cookies))
+        }
+        (Some(request_cookies), None) => Some(request_cookies.to_string()),
+        (None, jar_cookies) => jar_cookies,
+    }
+}
+
+fn remove_headers(headers: &mut Vec<(String, String)>, names: &[&str]) {
+    headers.retain(|(name, _)| {
+        !names
+            .iter()
+            .any(|removed| name.eq_ignore_ascii_case(removed))
+    });
+}
+
+async fn read_response(response: reqwest::Response) -> Result<HttpResponse, String> {
+    let status = response.status().as_u16();
+    let status_text = response
+        .status()
+        .canonical_reason()
+        .unwrap_or_default()
+        .to_string();
+    let url = response.url().to_string();
+    let headers = read_headers(&response);
+    let body = response.bytes().await.map_err(|e| e.to_string())?.to_vec();
+
+    Ok(HttpResponse {
+        status,
+        status_text,
+        url,
+        headers,
+        body,
+    })
+}
+
+fn read_headers(response: &reqwest::Response) -> Vec<(String, String)> {
     response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Value of the first header with the given name, ignoring case.
+pub fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_request_and_jar_cookies() {
+        assert_eq!(
+            merge_cookies(Some("a=1"), Some("b=2; c=3".to_string())).as_deref(),
+            Some("a=1; b=2; c=3")
+        );
+        assert_eq!(merge_cookies(Some("a=1"), None).as_deref(), Some("a=1"));
+        assert_eq!(
+            merge_cookies(None, Some("b=2".to_string())).as_deref(),
+            Some("b=2")
+        );
+        assert_eq!(merge_cookies(None, None), None);
+    }
+
+    #[test]
+    fn remove_headers_ignoring_case() {
+        let mut headers = vec![
+            ("Content-Type".to_string(), "text/plain".to_string()),
+            ("Accept".to_string(), "text/css".to_string()),
+            ("COOKIE".to_string(), "a=1".to_string()),
+            ("Authorization".to_string(), "Basic YTpi".to_string()),
+        ];
+
+        remove_headers(&mut headers, &REQUEST_BODY_HEADERS);
+        remove_headers(&mut headers, &CREDENTIAL_HEADERS);
+        assert_eq!(
+            headers,
+            vec![("Accept".to_string(), "text/css".to_string())]
+        );
+    }
 }
@@ -389,4 +389,80 @@ impl DisplayList {
     pub fn commands(self) -> Vec<Command> {
         self.0
     }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Command> {
+        self.0.iter()
+    }
+
+    pub fn dump(&self) -> String {
+        let mut result = String::new();
+        let mut level: usize = 0;
+
+        for command in self.0.iter() {
+            if let Command::EndClipRect = command {
+                level = level.saturating_sub(1);
+            }
+
+            result.push_str(&format!("{}{}\n", "|    ".repeat(level), command.dump()));
+
+            if let Command::ClipRect(_) = command {
+                level += 1;
+            }
+        }
+
+        result
+    }
+}
+
+impl Command {
+    pub fn dump(&self) -> String {
+        let get_rect_dimensions = |rect: &Rect| {
+            format!(
+                "(x: {} | y: {} | w: {} | h: {})",
+                rect.x, rect.y, rect.width, rect.height,
+            )
+        };
+
+        let get_color =
+            |color: &Color| format!("rgba({}, {}, {}, {})", color.r, color.g, color.b, color.a);
+
+        match self {
+            Command::FillRect(rect, color) => format!(
+                "[FillRect]{} {}",
+                get_rect_dimensions(rect),
+                get_color(color)
+            ),
+            Command::FillRRect(rrect, color) => format!(
+                "[FillRRect]{} {} {:?}",
+                get_rect_dimensions(&rrect.rect),
+                get_color(color),
+                rrect.corners
+            ),
+            Command::FillBorder(rect, border_rect, borders) => {
+                let get_border = |border: &Option<Border>| match border {
+                    Some(border) => format!("{:?} {}", border.style, get_color(&border.color)),
+                    None => "none".to_string(),
+                };
+                format!(
+                    "[FillBorder]{} Border Box: {} (top: {} | right: {} | bottom: {} | left: {})",
+                    get_rect_dimensions(rect),
+                    get_rect_dimensions(border_rect),
+                    get_border(&borders.top),
+                    get_border(&borders.right),
+                    get_border(&borders.bottom),
+                    get_border(&borders.left),
+                )
+            }
+            Command::FillText(content, rect, color, font_size, bold) => format!(
+                "[FillText]{} {} (font size: {} | bold: {}) {:?}",
+                get_rect_dimensions(rect),
+                get_color(color),
+                font_size,
+                bold,
+                content
+            ),
+            Command::ClipRect(rect) => format!("[ClipRect]{}", get_rect_dimensions(rect)),
+            Command::EndClipRect => "[EndClipRect]".to_string(),
+        }
+    }
 }
@@ -1,5 +1,6 @@
-mod display_list;
+pub mod display_list;
 mod painter;
+pub mod svg;
 mod utils;
 
 pub use painter::*;
@@ -29,10 +29,13 @@ impl Painter {
         result
     }
 
-    pub fn paint(&mut self, layout_box: &LayoutBoxPtr) {
+    /// Paint the layout tree onto the canvas and return the number of painted commands.
+    pub fn paint(&mut self, layout_box: &LayoutBoxPtr) -> usize {
         let display_list = DisplayListBuilder::new(&self.canvas_size).build(layout_box);
+        let commands = display_list.commands();
+        let commands_count = commands.len();
 
-        for command in display_list.commands() {
+        for command in commands {
             match command {
                 Command::FillRect(rect, color) => self.gfx.fill_rect(self.clip_rect(rect), color),
                 Command::FillRRect(rect, color) => self.gfx.fill_rrect(rect, color),
@@ -49,6 +52,8 @@ impl Painter {
                 }
             }
         }
+
+        commands_count
     }
 
     fn clip_rect(&self, rect: Rect) -> Rect {
@@ -0,0 +1,253 @@
+use shared::{
+    color::Color,
+    primitive::{RRect, Rect, Size},
+};
+
+use crate::display_list::{Borders, Command, DisplayList};
+
+/// Serialize a display list into an SVG document where every paint command
+/// becomes its own vector element.
+pub fn display_list_to_svg(display_list: &DisplayList, canvas_size: &Size) -> String {
+    let mut svg = SvgBuilder::new();
+
+    for command in display_list.iter() {
+        match command {
+            Command::FillRect(rect, color) => svg.fill_rect(rect, color),
+            Command::FillRRect(rect, color) => svg.fill_rrect(rect, color),
+            Command::FillBorder(rect, border_rect, borders) => {
+                svg.fill_borders(rect, border_rect, borders)
+            }
+            Command::FillText(content, rect, color, font_size, bold) => {
+                svg.fill_text(content, rect, color, *font_size, *bold)
+            }
+            Command::ClipRect(rect) => svg.clip_rect(rect),
+            Command::EndClipRect => svg.end_clip_rect(),
+        }
+    }
+
+    svg.finish(canvas_size)
+}
+
+struct SvgBuilder {
+    content: String,
+    level: usize,
+    clip_count: usize,
+}
+
+impl SvgBuilder {
+    fn new() -> Self {
+        Self {
+            content: String::new(),
+            level: 1,
+            clip_count: 0,
+        }
+    }
+
+    fn push_element(&mut self, element: String) {
+        self.content.push_str(&"  ".repeat(self.level));
+        self.content.push_str(&element);
+        self.content.push('\n');
+    }
+
+    fn fill_rect(&mut self, rect: &Rect, color: &Color) {
+        if color.a == 0 {
+            return;
+        }
+
+        self.push_element(format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            fill(color)
+        ));
+    }
+
+    fn fill_rrect(&mut self, rect: &RRect, color: &Color) {
+        if color.a == 0 {
+            return;
+        }
+
+        let corners = &rect.corners;
+        let (x, y, w, h) = (rect.x, rect.y, rect.width, rect.height);
+
+        let path = [
+            format!("M {} {}", x + corners.top_left.horizontal_r(), y),
+            format!("L {} {}", x + w - corners.top_right.horizontal_r(), y),
+            arc_to(
+                corners.top_right.horizontal_r(),
+                corners.top_right.vertical_r(),
+                x + w,
+                y + corners.top_right.vertical_r(),
+            ),
+            format!("L {} {}", x + w, y + h - corners.bottom_right.vertical_r()),
+            arc_to(
+                corners.bottom_right.horizontal_r(),
+                corners.bottom_right.vertical_r(),
+                x + w - corners.bottom_right.horizontal_r(),
+                y + h,
+            ),
+            format!("L {} {}", x + corners.bottom_left.horizontal_r(), y + h),
+            arc_to(
+                corners.bottom_left.horizontal_r(),
+                corners.bottom_left.vertical_r(),
+                x,
+                y + h - corners.bottom_left.vertical_r(),
+            ),
+            format!("L {} {}", x, y + corners.top_left.vertical_r()),
+            arc_to(
+                corners.top_left.horizontal_r(),
+                corners.top_left.vertical_r(),
+                x + corners.top_left.horizontal_r(),
+                y,
+            ),
+            "Z".to_string(),
+        ]
+        .join(" ");
+
+        self.push_element(format!("<path d=\"{}\" {}/>", path, fill(color)));
+    }
+
+    /// Each border edge is drawn as a trapezoid between the border box and the
+    /// padding box so that adjacent edges meet diagonally at the corners.
+    fn fill_borders(&mut self, rect: &Rect, border_rect: &Rect, borders: &Borders) {
+        let (inner_left, inner_top) = (rect.x, rect.y);
+        let (inner_right, inner_bottom) = (rect.x + rect.width, rect.y + rect.height);
+        let (outer_left, outer_top) = (border_rect.x, border_rect.y);
+        let (outer_right, outer_bottom) = (
+            border_rect.x + border_rect.width,
+            border_rect.y + border_rect.height,
+        );
+
+        let edges = [
+            (
+                &borders.top,
+                [
+                    (outer_left, outer_top),
+                    (outer_right, outer_top),
+                    (inner_right, inner_top),
+                    (inner_left, inner_top),
+                ],
+            ),
+            (
+                &borders.right,
+                [
+                    (outer_right, outer_top),
+                    (outer_right, outer_bottom),
+                    (inner_right, inner_bottom),
+                    (inner_right, inner_top),
+                ],
+            ),
+            (
+                &borders.bottom,
+                [
+                    (outer_right, outer_bottom),
+                    (outer_left, outer_bottom),
+                    (inner_left, inner_bottom),
+                    (inner_right, inner_bottom),
+                ],
+            ),
+            (
+                &borders.left,
+                [
+                    (outer_left, outer_bottom),
+                    (outer_left, outer_top),
+                    (inner_left, inner_top),
+                    (inner_left, inner_bottom),
+                ],
+            ),
+        ];
+
+        for (border, points) in edges.iter() {
+            let border = match border {
+                Some(border) if border.color.a > 0 => border,
+                _ => continue,
+            };
+
+            let points = points
+                .iter()
+                .map(|(x, y)| format!("{},{}", x, y))
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            self.push_element(format!(
+                "<polygon points=\"{}\" {}/>",
+                points,
+                fill(&border.color)
+            ));
+        }
+    }
+
+    fn fill_text(&mut self, content: &str, rect: &Rect, color: &Color, font_size: f32, bold: bool) {
+        let font_weight = if bold { "bold" } else { "normal" };
+
+        self.push_element(format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-weight=\"{}\" dominant-baseline=\"text-before-edge\" xml:space=\"preserve\" {}>{}</text>",
+            rect.x,
+            rect.y,
+            font_size,
+            font_weight,
+            fill(color),
+            escape(content)
+        ));
+    }
+
+    fn clip_rect(&mut self, rect: &Rect) {
+        self.clip_count += 1;
+        let id = format!("clip{}", self.clip_count);
+
+        self.push_element(format!(
+            "<clipPath id=\"{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/></clipPath>",
+            id, rect.x, rect.y, rect.width, rect.height
+        ));
+        self.push_element(format!("<g clip-path=\"url(#{})\">", id));
+        self.level += 1;
+    }
+
+    fn end_clip_rect(&mut self) {
+        if self.level > 1 {
+            self.level -= 1;
+            self.push_element("</g>".to_string());
+        }
+    }
+
+    fn finish(mut self, canvas_size: &Size) -> String {
+        while self.level > 1 {
+            self.end_clip_rect();
+        }
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n  <rect width=\"100%\" height=\"100%\" fill=\"rgb(255, 255, 255)\"/>\n{}</svg>\n",
+            self.content,
+            w = canvas_size.width,
+            h = canvas_size.height,
+        )
+    }
+}
+
+fn arc_to(rx: f32, ry: f32, x: f32, y: f32) -> String {
+    format!("A {} {} 0 0 1 {} {}", rx, ry, x, y)
+}
+
+fn fill(color: &Color) -> String {
+    if color.a == 255 {
+        return format!("fill=\"rgb({}, {}, {})\"", color.r, color.g, color.b);
+    }
+
+    format!(
+        "fill=\"rgb({}, {}, {})\" fill-opacity=\"{}\"",
+        color.r,
+        color.g,
+        color.b,
+        color.a as f32 / 255.
+    )
+}
+
+fn escape(content: &str) -> String {
+    content
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,26 +1,66 @@
 use std::str::Chars;
 
-use encoding::{all::UTF_8, decode};
+use encoding::{all::UTF_8, decode, label::encoding_from_whatwg_label};
+
+use crate::charset;
 
 pub struct ByteString {
     content: String,
+    charset: String,
 }
 
 impl ByteString {
     pub fn new(bytes: &[u8]) -> Self {
-        let decode_result = decode(bytes, encoding::DecoderTrap::Replace, UTF_8);
+        Self::with_charset(bytes, None)
+    }
+
+    /// Decode an HTML document, sniffing its encoding from its content when the
+    /// `Content-Type` charset is missing.
+    pub fn from_html(bytes: &[u8], transport_charset: Option<&str>) -> Self {
+        let charset = charset::html_charset(bytes, transport_charset);
+        Self::with_charset(bytes, Some(&charset))
+    }
+
+    /// Decode a stylesheet. `environment_charset` is the encoding of the
+    /// document that linked it, used when the stylesheet declares none.
+    pub fn from_css(
+        bytes: &[u8],
+        transport_charset: Option<&str>,
+        environment_charset: Option<&str>,
+    ) -> Self {
+        let charset = charset::css_charset(bytes, transport_charset, environment_charset);
+        Self::with_charset(bytes, Some(&charset))
+    }
+
+    /// Decode the bytes with the encoding of the given charset label, e.g. from
+    /// a `Content-Type` header. Unknown labels fall back to UTF-8 and a byte
+    /// order mark overrides the label.
+    pub fn with_charset(bytes: &[u8], charset: Option<&str>) -> Self {
+        let encoding = charset
+            .and_then(encoding_from_whatwg_label)
+            .unwrap_or(UTF_8);
+        let decode_result = decode(bytes, encoding::DecoderTrap::Replace, encoding);
 
         match decode_result {
-            (Ok(result), _) => Self { content: result },
-            _ => {
+            (Ok(result), encoding) => Self {
+                content: result,
+                charset: encoding.whatwg_name().unwrap_or("utf-8").to_string(),
+            },
+            (Err(_), encoding) => {
                 log::debug!("Unable to decode text bytes");
                 Self {
                     content: String::new(),
+                    charset: encoding.whatwg_name().unwrap_or("utf-8").to_string(),
                 }
             }
         }
     }
 
+    /// The name of the encoding the bytes were decoded with.
+    pub fn charset(&self) -> &str {
+        &self.charset
+    }
+
     pub fn chars(&self) -> Chars {
         self.content.chars()
     }
@@ -0,0 +1,273 @@
+use encoding::label::encoding_from_whatwg_label;
+
+/// How many bytes of a document are scanned for a `<meta>` declaring its charset.
+const PRESCAN_LENGTH: usize = 1024;
+
+/// Determine the encoding of an HTML document from its byte order mark, the
+/// charset of its `Content-Type` or a `<meta>` in its first bytes, in this
+/// order. Documents without any of them are decoded as UTF-8.
+/// https://html.spec.whatwg.org/multipage/parsing.html#encoding-sniffing-algorithm
+pub fn html_charset(bytes: &[u8], transport_charset: Option<&str>) -> String {
+    bom_charset(bytes)
+        .map(|charset| charset.to_string())
+        .or_else(|| transport_charset.and_then(normalize))
+        .or_else(|| prescan_meta_charset(bytes).map(declared_charset))
+        .unwrap_or_else(|| "utf-8".to_string())
+}
+
+/// Determine the encoding of a stylesheet from its byte order mark, the charset
+/// of its `Content-Type`, its `@charset` rule or the encoding of the document
+/// that linked it, in this order.
+/// https://drafts.csswg.org/css-syntax/#determine-the-fallback-encoding
+pub fn css_charset(
+    bytes: &[u8],
+    transport_charset: Option<&str>,
+    environment_charset: Option<&str>,
+) -> String {
+    bom_charset(bytes)
+        .map(|charset| charset.to_string())
+        .or_else(|| transport_charset.and_then(normalize))
+        .or_else(|| at_charset_rule(bytes).map(declared_charset))
+        .or_else(|| environment_charset.and_then(normalize))
+        .unwrap_or_else(|| "utf-8".to_string())
+}
+
+pub fn bom_charset(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        Some("utf-8")
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        Some("utf-16be")
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        Some("utf-16le")
+    } else {
+        None
+    }
+}
+
+/// The canonical name of the encoding of a label, e.g. `windows-1252` for `latin1`.
+fn normalize(label: &str) -> Option<String> {
+    encoding_from_whatwg_label(label.trim()).and_then(|encoding| {
+        encoding
+            .whatwg_name()
+            .map(|name| name.to_string())
+            // Encodings without a WHATWG name are internal to the decoder.
+            .or_else(|| Some(encoding.name().to_string()))
+    })
+}
+
+/// A charset declared inside ASCII-compatible content cannot be UTF-16, as the
+/// declaration could not have been read otherwise.
+fn declared_charset(charset: String) -> String {
+    match charset.as_str() {
+        "utf-16be" | "utf-16le" => "utf-8".to_string(),
+        "x-user-defined" => "windows-1252".to_string(),
+        _ => charset,
+    }
+}
+
+/// `@charset "<label>";` at the very start of a stylesheet.
+fn at_charset_rule(bytes: &[u8]) -> Option<String> {
+    let rest = bytes.strip_prefix(b"@charset \"")?;
+    let end = rest.iter().position(|&byte| byte == b'"')?;
+    if rest.get(end + 1) != Some(&b';') {
+        return None;
+    }
+    normalize(std::str::from_utf8(&rest[..end]).ok()?)
+}
+
+/// A simplified version of the prescan of a byte stream to determine its encoding.
+/// https://html.spec.whatwg.org/multipage/parsing.html#prescan-a-byte-stream-to-determine-its-encoding
+fn prescan_meta_charset(bytes: &[u8]) -> Option<String> {
+    let bytes = &bytes[..bytes.len().min(PRESCAN_LENGTH)];
+    let mut position = 0;
+
+    while position < bytes.len() {
+        let rest = &bytes[position..];
+
+        if rest.starts_with(b"<!--") {
+            position += find(&rest[4..], b"-->").map_or(rest.len(), |end| end + 7);
+        } else if starts_with_ignore_case(rest, b"<meta")
+            && rest
+                .get(5)
+                .is_some_and(|&byte| is_space(byte) || byte == b'/')
+        {
+            position += 5;
+            if let Some(charset) = meta_charset(bytes, &mut position) {
+                return Some(charset);
+            }
+        } else if rest.len() > 1 && rest[0] == b'<' && is_tag_start(&rest[1..]) {
+            // Skip the tag with its attributes, whose values might contain `>`.
+            position += 1;
+            while read_attribute(bytes, &mut position).is_some() {}
+        } else if rest.starts_with(b"<!") || rest.starts_with(b"</") || rest.starts_with(b"<?") {
+            position += find(rest, b">").map_or(rest.len(), |end| end + 1);
+        } else {
+            position += 1;
+        }
+    }
+
+    None
+}
+
+/// The charset declared by the attributes of a `<meta>`, either with `charset`
+/// or with `http-equiv="content-type"` and a `content` holding a charset.
+fn meta_charset(bytes: &[u8], position: &mut usize) -> Option<String> {
+    let mut is_content_type = false;
+    let mut charset = None;
+    let mut content_charset = None;
+
+    while let Some((name, value)) = read_attribute(bytes, position) {
+        match name.as_str() {
+            "http-equiv" => is_content_type = value.eq_ignore_ascii_case("content-type"),
+            "charset" if charset.is_none() => charset = Some(value),
+            "content" if content_charset.is_none() => {
+                content_charset = charset_from_content(&value)
+            }
+            _ => {}
+        }
+    }
+
+    charset
+        .or(content_charset.filter(|_| is_content_type))
+        .and_then(|label| normalize(&label))
+}
+
+/// Extract the charset from the `content` of a `<meta http-equiv>`, e.g. from
+/// `text/html; charset=iso-8859-2`.
+fn charset_from_content(content: &str) -> Option<String> {
+    let lowercase = content.to_ascii_lowercase();
+    let start = lowercase.find("charset")? + "charset".len();
+    let value = lowercase[start..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+
+    let charset = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+        _ => value
+            .split(|c: char| c == ';' || c.is_ascii_whitespace())
+            .next()?,
+    };
+    Some(charset.to_string()).filter(|charset| !charset.is_empty())
+}
+
+/// Read the next attribute of a tag, or `None` at the end of the tag.
+/// https://html.spec.whatwg.org/multipage/parsing.html#concept-get-attributes-when-sniffing
+fn read_attribute(bytes: &[u8], position: &mut usize) -> Option<(String, String)> {
+    while *position < bytes.len() && (is_space(bytes[*position]) || bytes[*position] == b'/') {
+        *position += 1;
+    }
+    if *position >= bytes.len() || bytes[*position] == b'>' {
+        *position += 1;
+        return None;
+    }
+
+    let mut name = String::new();
+    while *position < bytes.len() {
+        match bytes[*position] {
+            b'=' if !name.is_empty() => break,
+            byte if is_space(byte) || byte == b'/' || byte == b'>' => break,
+            byte => name.push(byte.to_ascii_lowercase() as char),
+        }
+        *position += 1;
+    }
+
+    while *position < bytes.len() && is_space(bytes[*position]) {
+        *position += 1;
+    }
+    if bytes.get(*position) != Some(&b'=') {
+        return Some((name, String::new()));
+    }
+    *position += 1;
+    while *position < bytes.len() && is_space(bytes[*position]) {
+        *position += 1;
+    }
+
+    let mut value = String::new();
+    match bytes.get(*position) {
+        Some(&quote) if quote == b'"' || quote == b'\'' => {
+            *position += 1;
+            while *position < bytes.len() && bytes[*position] != quote {
+                value.push(bytes[*position].to_ascii_lowercase() as char);
+                *position += 1;
+            }
+            *position += 1;
+        }
+        _ => {
+            while *position < bytes.len() && !is_space(bytes[*position]) && bytes[*position] != b'>'
+            {
+                value.push(bytes[*position].to_ascii_lowercase() as char);
+                *position += 1;
+            }
+        }
+    }
+
+    Some((name, value))
+}
+
+fn is_tag_start(bytes: &[u8]) -> bool {
+    match bytes {
+        [b'/', next, ..] => next.is_ascii_alphabetic(),
+        [first, ..] => first.is_ascii_alphabetic(),
+        [] => false,
+    }
+}
+
+fn is_space(byte: u8) -> bool {
+    matches!(byte, b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+fn find(bytes: &[u8], needle: &[u8]) -> Option<usize> {
+    bytes
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_charset_precedence() {
+        let html = b"<meta charset=\"iso-8859-2\"><p>x</p>";
+
+        assert_eq!(html_charset(html, None), "iso-8859-2");
+        assert_eq!(html_charset(html, Some("Shift_JIS")), "shift_jis");
+        assert_eq!(
+            html_charset(b"\xEF\xBB\xBF<p>x</p>", Some("latin1")),
+            "utf-8"
+        );
+        assert_eq!(html_charset(b"<p>x</p>", Some("unknown")), "utf-8");
+    }
+
+    #[test]
+    fn prescan_meta() {
+        let http_equiv = b"<!DOCTYPE html><!-- <meta charset=koi8-r> --><head>\
+            <title a='>'>x</title>\
+            <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=latin1\">";
+
+        assert_eq!(html_charset(http_equiv, None), "windows-1252");
+        assert_eq!(html_charset(b"<meta charset=utf-16le>", None), "utf-8");
+        assert_eq!(
+            html_charset(b"<meta content=\"text/html; charset=koi8-r\">", None),
+            "utf-8"
+        );
+    }
+
+    #[test]
+    fn css_charset_precedence() {
+        let css = b"@charset \"windows-1250\"; p { color: red }";
+
+        assert_eq!(css_charset(css, None, None), "windows-1250");
+        assert_eq!(css_charset(css, Some("utf-8"), None), "utf-8");
+        assert_eq!(
+            css_charset(b"p {}", None, Some("iso-8859-1")),
+            "windows-1252"
+        );
+        assert_eq!(css_charset(b"@charset 'latin1'; p {}", None, None), "utf-8");
+    }
+}
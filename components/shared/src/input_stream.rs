@@ -60,8 +60,18 @@ where
         self.buffer.front().map(|i| i.clone())
     }
 
+    /// Buffer items until `n` of them are available or the source is exhausted.
+    fn fill_buffer(&mut self, n: usize) {
+        while self.buffer.len() < n {
+            match self.source.next() {
+                Some(item) => self.buffer.push_back(item),
+                None => break,
+            }
+        }
+    }
+
     pub fn peek_next(&mut self, n: usize) -> Option<Vec<I>> {
-        self.consume_source_to_buffer();
+        self.fill_buffer(n);
 
         if self.buffer.len() < n {
             return None;
@@ -86,7 +96,7 @@ where
     }
 
     pub fn peek_next_as<S: FromIterator<I>>(&mut self, n: usize) -> Option<S> {
-        self.consume_source_to_buffer();
+        self.fill_buffer(n);
 
         if self.buffer.len() < n {
             return None;
@@ -132,3 +142,21 @@ where
         self.is_reconsume = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peek_several_items_ahead() {
+        let mut stream = CharInputStream::new("@charset".chars());
+
+        assert_eq!(stream.next(), Some('@'));
+        assert_eq!(stream.peek_next_as::<String>(3), Some("cha".to_string()));
+        assert_eq!(stream.next(), Some('c'));
+
+        stream.reconsume();
+        assert_eq!(stream.peek_next_as::<String>(3), Some("cha".to_string()));
+        assert_eq!(stream.peek_next_as::<String>(10), None);
+    }
+}
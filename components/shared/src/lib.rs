@@ -1,4 +1,5 @@
 pub mod byte_string;
+pub mod charset;
 pub mod color;
 pub mod data_stream;
 pub mod input_stream;
@@ -1,4 +1,5 @@
 use super::selector_matching::is_match_selectors;
+use css::cssom::style_rule::StyleRule;
 use css::parser::structs::ComponentValue;
 use css::parser::structs::Declaration;
 use css::selector::structs::Specificity;
@@ -78,9 +79,12 @@ fn collect_declared_values(node: &NodePtr, rules: &[ContextualRule]) -> Declared
         return result;
     }
 
+    let inline_rule = inline_style_rule(node);
+
     let matched_rules = rules
         .iter()
         .filter(|rule| is_match_selectors(node, &rule.inner.selectors))
+        .chain(inline_rule.iter())
         .collect::<Vec<&ContextualRule>>();
 
     let mut insert_declaration =
@@ -136,6 +140,22 @@ fn collect_declared_values(node: &NodePtr, rules: &[ContextualRule]) -> Declared
     result
 }
 
+/// Wrap the declarations of the element's `style` attribute in a rule that
+/// only applies to that element.
+fn inline_style_rule(node: &NodePtr) -> Option<ContextualRule> {
+    let declarations = node.as_element().inline_style();
+
+    if declarations.is_empty() {
+        return None;
+    }
+
+    Some(ContextualRule {
+        inner: StyleRule::new(Vec::new(), declarations),
+        origin: CascadeOrigin::Author,
+        location: CSSLocation::Inline,
+    })
+}
+
 /// The implementation for ordering for cascade sort
 ///
 /// These are the steps to compare the order:
@@ -221,9 +241,14 @@ fn cmp_cascade_origin(a: &PropertyDeclaration, b: &PropertyDeclaration) -> Order
 #[cfg(test)]
 mod tests {
     use super::*;
+    use css::cssom::css_rule::CSSRule;
     use css::parser::structs::ComponentValue;
     use css::tokenizer::token::Token;
+    use dom::create_element;
+    use shared::tree_node::WeakTreeNode;
     use style_types::values::prelude::{Color, Percentage};
+    use test_utils::css::parse_stylesheet;
+    use test_utils::dom_creator::document;
 
     #[test]
     fn cascade_simple() {
@@ -280,6 +305,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn inline_style_wins_over_stylesheet() {
+        let element = create_element(WeakTreeNode::from(&document().0), "h1");
+        element.as_element().set_attribute("id", "title");
+        element
+            .as_element()
+            .set_attribute("style", "color: black; color: transparent");
+
+        let stylesheet = parse_stylesheet("h1#title { color: red; width: 20%; }");
+        let rules = stylesheet
+            .iter()
+            .map(|rule| match rule {
+                CSSRule::Style(style) => ContextualRule {
+                    inner: style.clone(),
+                    origin: CascadeOrigin::Author,
+                    location: CSSLocation::Embedded,
+                },
+            })
+            .collect::<Vec<ContextualRule>>();
+
+        let properties = collect_cascaded_values(&element, &rules);
+
+        assert_eq!(
+            properties.get(&Property::Color),
+            Some(&Value::Color(Color::transparent()))
+        );
+        assert_eq!(
+            properties.get(&Property::Width),
+            Some(&Value::Percentage(Percentage(20.0.into())))
+        );
+    }
+
     #[test]
     fn parse_multiple_value_override() {
         let a = PropertyDeclaration {
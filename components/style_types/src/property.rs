@@ -1,8 +1,10 @@
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::*;
 
 /// CSS property name
-#[derive(Debug, Clone, Hash, Eq, PartialEq, EnumIter)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Property {
     BackgroundColor,
     Color,
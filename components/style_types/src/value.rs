@@ -1,4 +1,5 @@
 use css::{parser::structs::ComponentValue, tokenizer::token::Token};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     property::Property,
@@ -53,7 +54,7 @@ macro_rules! parse_value {
 }
 
 /// CSS property value
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Value {
     Color(Color),
     Display(Display),
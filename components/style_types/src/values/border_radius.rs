@@ -1,8 +1,9 @@
 use super::prelude::{Length, LengthPercentage};
 use css::parser::structs::ComponentValue;
 use css::tokenizer::token::Token;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub struct BorderRadius(pub LengthPercentage, pub LengthPercentage);
 
 impl BorderRadius {
@@ -1,7 +1,8 @@
 use css::parser::structs::ComponentValue;
 use css::tokenizer::token::Token;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub enum BorderStyle {
     Hidden,
     Dotted,
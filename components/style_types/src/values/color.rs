@@ -2,8 +2,9 @@ use super::number::Number;
 use css::parser::structs::ComponentValue;
 use css::parser::structs::Function;
 use css::tokenizer::token::Token;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Color {
     CurrentColor,
     Rgba(Number, Number, Number, Number),
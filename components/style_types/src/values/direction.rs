@@ -1,7 +1,8 @@
 use css::parser::structs::ComponentValue;
 use css::tokenizer::token::Token;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Direction {
     Ltr,
     Rtl,
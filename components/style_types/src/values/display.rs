@@ -1,20 +1,21 @@
 use css::parser::structs::ComponentValue;
 use css::tokenizer::token::Token;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Display {
     Full(OuterDisplayType, InnerDisplayType),
     Box(DisplayBox),
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum OuterDisplayType {
     Block,
     Inline,
     RunIn,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum InnerDisplayType {
     Flow,
     FlowRoot,
@@ -23,7 +24,7 @@ pub enum InnerDisplayType {
     Grid,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum DisplayBox {
     Contents,
     None,
@@ -1,8 +1,9 @@
 use css::{parser::structs::ComponentValue, tokenizer::token::Token};
+use serde::{Deserialize, Serialize};
 
 use super::number::Number;
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
 pub struct FontWeight(pub Number);
 
 impl Eq for FontWeight {}
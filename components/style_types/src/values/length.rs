@@ -1,8 +1,9 @@
 use super::number::Number;
 use css::parser::structs::ComponentValue;
 use css::tokenizer::token::Token;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Length {
     pub value: Number,
     pub unit: LengthUnit,
@@ -10,7 +11,7 @@ pub struct Length {
 
 impl Eq for Length {}
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum LengthUnit {
     Rem,
     Em,
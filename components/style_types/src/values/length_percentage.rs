@@ -1,9 +1,10 @@
 use css::parser::structs::ComponentValue;
+use serde::{Deserialize, Serialize};
 
 use super::length::Length;
 use super::percentage::Percentage;
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub enum LengthPercentage {
     Length(Length),
     Percentage(Percentage),
@@ -0,0 +1,179 @@
+use crate::{encode::percent_decode, Url};
+
+/// The content of a `data:` URL, processed as described in
+/// https://fetch.spec.whatwg.org/#data-url-processor
+#[derive(Debug, PartialEq, Eq)]
+pub struct DataUrl {
+    /// The MIME type without its parameters, e.g. `text/css`.
+    pub mime_type: String,
+    pub charset: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl DataUrl {
+    pub fn parse(url: &Url) -> Option<DataUrl> {
+        if url.scheme != "data" {
+            return None;
+        }
+
+        // The fragment is not part of the data, but the query is.
+        let mut input = url.path.as_str();
+        if let Some(query) = &url.query {
+            input.push('?');
+            input.push_str(query);
+        }
+
+        let (mime_type, body) = input.split_once(',')?;
+        let mime_type = mime_type.trim_matches(|c: char| c.is_ascii_whitespace());
+        let mut body = percent_decode(body.as_bytes());
+
+        let (mime_type, is_base64) = match strip_base64_suffix(mime_type) {
+            Some(mime_type) => (mime_type, true),
+            None => (mime_type, false),
+        };
+        if is_base64 {
+            body = forgiving_base64_decode(&body)?;
+        }
+
+        let mime_type = if mime_type.starts_with(';') {
+            format!("text/plain{}", mime_type)
+        } else {
+            mime_type.to_string()
+        };
+
+        let (essence, charset) = match parse_mime_type(&mime_type) {
+            Some(parsed) => parsed,
+            None => ("text/plain".to_string(), Some("US-ASCII".to_string())),
+        };
+
+        Some(DataUrl {
+            mime_type: essence,
+            charset,
+            body,
+        })
+    }
+
+    /// The MIME type with its charset, as sent in a `Content-Type` header.
+    pub fn content_type(&self) -> String {
+        match &self.charset {
+            Some(charset) => format!("{};charset={}", self.mime_type, charset),
+            None => self.mime_type.clone(),
+        }
+    }
+}
+
+/// Remove a trailing `;base64`, ignoring case and spaces before `base64`.
+fn strip_base64_suffix(mime_type: &str) -> Option<&str> {
+    let (rest, parameter) = mime_type.rsplit_once(';')?;
+    let parameter = parameter.trim_matches(|c: char| c.is_ascii_whitespace());
+    if parameter.eq_ignore_ascii_case("base64") {
+        Some(rest)
+    } else {
+        None
+    }
+}
+
+/// Split a MIME type into its lowercase essence & its charset parameter.
+fn parse_mime_type(input: &str) -> Option<(String, Option<String>)> {
+    let mut parts = input.split(';');
+    let essence = parts.next()?.trim().to_lowercase();
+    let (kind, subtype) = essence.split_once('/')?;
+    if kind.is_empty() || subtype.is_empty() || essence.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let charset = parts
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_string());
+
+    Some((essence, charset))
+}
+
+/// https://infra.spec.whatwg.org/#forgiving-base64-decode
+fn forgiving_base64_decode(input: &[u8]) -> Option<Vec<u8>> {
+    let mut data: Vec<u8> = input
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+
+    if data.len() % 4 == 0 {
+        if data.ends_with(b"==") {
+            data.truncate(data.len() - 2);
+        } else if data.ends_with(b"=") {
+            data.truncate(data.len() - 1);
+        }
+    }
+
+    if data.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut output = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in data {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::URLParser;
+
+    fn parse(input: &str) -> Option<DataUrl> {
+        DataUrl::parse(&URLParser::parse(input, None).unwrap())
+    }
+
+    #[test]
+    fn percent_encoded_data_url() {
+        let data_url =
+            parse("data:text/css;charset=utf-8,p%20%7B%20color:%20red%20%7D#top").unwrap();
+
+        assert_eq!(data_url.mime_type, "text/css");
+        assert_eq!(data_url.charset, Some("utf-8".to_string()));
+        assert_eq!(data_url.body, b"p { color: red }");
+    }
+
+    #[test]
+    fn base64_data_url() {
+        let data_url = parse("data:text/html;BASE64,PHA+aGk8L3A+").unwrap();
+
+        assert_eq!(data_url.mime_type, "text/html");
+        assert_eq!(data_url.charset, None);
+        assert_eq!(data_url.body, b"<p>hi</p>");
+        assert!(parse("data:;base64,abc=d").is_none());
+    }
+
+    #[test]
+    fn default_mime_type() {
+        let data_url = parse("data:,hello").unwrap();
+
+        assert_eq!(data_url.mime_type, "text/plain");
+        assert_eq!(data_url.charset, Some("US-ASCII".to_string()));
+        assert_eq!(data_url.content_type(), "text/plain;charset=US-ASCII");
+
+        let data_url = parse("data:;charset=utf-8,h%C3%A9").unwrap();
+        assert_eq!(data_url.mime_type, "text/plain");
+        assert_eq!(String::from_utf8(data_url.body).unwrap(), "hé");
+    }
+}
@@ -10,6 +10,7 @@ pub enum PercentEncodeSet {
     SpecialQuery,
     Fragment,
     C0Control,
+    FormUrlencoded,
 }
 
 impl URLPercentEncode {
@@ -26,7 +27,7 @@ impl URLPercentEncode {
             } else if !percent_encode_set.contains(*byte) {
                 output.push(*byte as char);
             } else {
-                output.push_str(&format!("%{:X?}", *byte as u32));
+                output.push_str(&format!("%{:02X}", byte));
             }
         }
 
@@ -34,6 +35,42 @@ impl URLPercentEncode {
     }
 }
 
+/// https://url.spec.whatwg.org/#percent-decode
+/// Sequences that are not followed by two hex digits are left as they are.
+pub fn percent_decode(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut index = 0;
+
+    while index < input.len() {
+        let byte = input[index];
+        let decoded = input
+            .get(index + 1..index + 3)
+            .filter(|hex| byte == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match decoded {
+            Some(decoded) => {
+                output.push(decoded);
+                index += 3;
+            }
+            None => {
+                output.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    output
+}
+
+/// Percent-decode a string, replacing the sequences that do not decode to
+/// UTF-8 with U+FFFD.
+/// https://url.spec.whatwg.org/#string-percent-decode
+pub fn percent_decode_str(input: &str) -> String {
+    String::from_utf8_lossy(&percent_decode(input.as_bytes())).into_owned()
+}
+
 impl PercentEncodeSet {
     pub fn contains(&self, c: u8) -> bool {
         match self {
@@ -63,7 +100,7 @@ impl PercentEncodeSet {
             Self::UserInfo => {
                 PercentEncodeSet::Path.contains(c)
                     || match c {
-                        0x002F | 0x003A | 0x003B | 0x003D | 0x0040 | 0x005B | 0x005E | 0x007C => {
+                        0x002F | 0x003A | 0x003B | 0x003D | 0x0040 | 0x005B..=0x005E | 0x007C => {
                             true
                         }
                         _ => false,
@@ -76,6 +113,10 @@ impl PercentEncodeSet {
                         _ => false,
                     }
             }
+            Self::FormUrlencoded => {
+                PercentEncodeSet::Component.contains(c)
+                    || matches!(c, 0x0021 | 0x0027..=0x0029 | 0x007E)
+            }
         }
     }
 }
@@ -102,5 +143,18 @@ mod tests {
             URLPercentEncode::encode("‽".as_bytes(), PercentEncodeSet::UserInfo, false),
             "%E2%80%BD"
         );
+        assert_eq!(
+            URLPercentEncode::encode("\t".as_bytes(), PercentEncodeSet::UserInfo, false),
+            "%09"
+        );
+    }
+
+    #[test]
+    fn simple_percent_decode() {
+        assert_eq!(percent_decode(b"hello%20world"), b"hello world");
+        assert_eq!(percent_decode(b"%E2%89%a1"), "≡".as_bytes());
+        assert_eq!(percent_decode(b"100%"), b"100%");
+        assert_eq!(percent_decode(b"%zz%+1%2"), b"%zz%+1%2");
+        assert_eq!(percent_decode_str("caf%C3%A9%FF"), "café\u{FFFD}");
     }
 }
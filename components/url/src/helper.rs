@@ -84,3 +84,9 @@ pub fn contains_forbidden_host_code_point(input: &str) -> bool {
         .contains(&c)
     })
 }
+
+/// https://url.spec.whatwg.org/#forbidden-domain-code-point
+pub fn contains_forbidden_domain_code_point(input: &str) -> bool {
+    contains_forbidden_host_code_point(input)
+        || input.contains(|c| is_c0_control(c as u32) || c == '%' || c == '\u{7F}')
+}
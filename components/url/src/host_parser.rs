@@ -1,6 +1,8 @@
 use crate::{
-    encode::{PercentEncodeSet, URLPercentEncode},
-    helper::{contains_forbidden_host_code_point, is_url_c},
+    encode::{percent_decode, PercentEncodeSet, URLPercentEncode},
+    helper::{contains_forbidden_domain_code_point, contains_forbidden_host_code_point, is_url_c},
+    punycode,
+    url::Host,
 };
 
 pub struct HostParser;
@@ -10,24 +12,248 @@ fn report_validation_error() {
 }
 
 impl HostParser {
-    pub fn parse(input: &str, is_not_special: bool) -> Option<String> {
-        if input.starts_with("[") {
-            if !input.ends_with("]") {
+    /// https://url.spec.whatwg.org/#concept-host-parser
+    pub fn parse(input: &str, is_not_special: bool) -> Option<Host> {
+        if let Some(address) = input.strip_prefix('[') {
+            let Some(address) = address.strip_suffix(']') else {
                 report_validation_error();
                 return None;
-            }
-
-            unimplemented!("IPv6 parsing is not supported yet");
+            };
+            return HostParser::parse_ipv6(address).map(Host::Ipv6);
         }
 
         if is_not_special {
             return HostParser::parse_opaque_host(input);
         }
 
-        return Some(input.to_string());
+        let domain = String::from_utf8_lossy(&percent_decode(input.as_bytes())).to_string();
+        let ascii_domain = HostParser::domain_to_ascii(&domain)?;
+
+        if contains_forbidden_domain_code_point(&ascii_domain) {
+            report_validation_error();
+            return None;
+        }
+
+        if ends_in_a_number(&ascii_domain) {
+            return HostParser::parse_ipv4(&ascii_domain).map(Host::Ipv4);
+        }
+
+        Some(Host::Domain(ascii_domain))
+    }
+
+    /// A simplified version of domain to ASCII: the domain is lowercased and its
+    /// labels that are not ASCII are encoded with Punycode, but the rest of the
+    /// UTS #46 mapping table is not applied.
+    /// https://url.spec.whatwg.org/#concept-domain-to-ascii
+    fn domain_to_ascii(domain: &str) -> Option<String> {
+        let domain = domain
+            .to_lowercase()
+            .replace(['\u{3002}', '\u{FF0E}', '\u{FF61}'], ".");
+
+        let labels = domain
+            .split('.')
+            .map(|label| {
+                if !label.is_ascii() {
+                    return punycode::encode(label).map(|encoded| format!("xn--{}", encoded));
+                }
+                if let Some(encoded) = label.strip_prefix("xn--") {
+                    // Labels that claim to be Punycode must decode to something.
+                    punycode::decode(encoded).filter(|decoded| !decoded.is_empty())?;
+                }
+                Some(label.to_string())
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let ascii_domain = labels.join(".");
+        if ascii_domain.is_empty() {
+            report_validation_error();
+            return None;
+        }
+        Some(ascii_domain)
+    }
+
+    /// https://url.spec.whatwg.org/#concept-ipv4-parser
+    fn parse_ipv4(input: &str) -> Option<u32> {
+        let mut parts: Vec<&str> = input.split('.').collect();
+        if parts.last() == Some(&"") {
+            report_validation_error();
+            if parts.len() > 1 {
+                parts.pop();
+            }
+        }
+
+        if parts.len() > 4 {
+            report_validation_error();
+            return None;
+        }
+
+        let numbers = parts
+            .iter()
+            .map(|part| parse_ipv4_number(part))
+            .collect::<Option<Vec<u64>>>()?;
+
+        let (last, rest) = numbers.split_last()?;
+        if rest.iter().any(|&number| number > 255) {
+            report_validation_error();
+            return None;
+        }
+        if *last >= 256u64.pow(5 - numbers.len() as u32) {
+            report_validation_error();
+            return None;
+        }
+
+        let address = rest
+            .iter()
+            .enumerate()
+            .fold(*last, |address, (counter, number)| {
+                address + number * 256u64.pow(3 - counter as u32)
+            });
+        Some(address as u32)
+    }
+
+    /// https://url.spec.whatwg.org/#concept-ipv6-parser
+    fn parse_ipv6(input: &str) -> Option<[u16; 8]> {
+        let input: Vec<char> = input.chars().collect();
+        let c = |pointer: usize| input.get(pointer).copied();
+
+        let mut address = [0u16; 8];
+        let mut piece_index = 0;
+        let mut compress = None;
+        let mut pointer = 0;
+
+        if c(pointer) == Some(':') {
+            if c(pointer + 1) != Some(':') {
+                report_validation_error();
+                return None;
+            }
+            pointer += 2;
+            piece_index += 1;
+            compress = Some(piece_index);
+        }
+
+        while c(pointer).is_some() {
+            if piece_index == 8 {
+                report_validation_error();
+                return None;
+            }
+
+            if c(pointer) == Some(':') {
+                if compress.is_some() {
+                    report_validation_error();
+                    return None;
+                }
+                pointer += 1;
+                piece_index += 1;
+                compress = Some(piece_index);
+                continue;
+            }
+
+            let mut value: u16 = 0;
+            let mut length = 0;
+            while length < 4 {
+                match c(pointer).and_then(|c| c.to_digit(16)) {
+                    Some(digit) => {
+                        value = value * 0x10 + digit as u16;
+                        pointer += 1;
+                        length += 1;
+                    }
+                    None => break,
+                }
+            }
+
+            match c(pointer) {
+                Some('.') => {
+                    // The last two pieces are written as an IPv4 address.
+                    if length == 0 || piece_index > 6 {
+                        report_validation_error();
+                        return None;
+                    }
+                    pointer -= length;
+
+                    let mut numbers_seen = 0;
+                    while c(pointer).is_some() {
+                        if numbers_seen > 0 {
+                            if c(pointer) == Some('.') && numbers_seen < 4 {
+                                pointer += 1;
+                            } else {
+                                report_validation_error();
+                                return None;
+                            }
+                        }
+
+                        let mut ipv4_piece: Option<u16> = None;
+                        while let Some(number) = c(pointer).and_then(|c| c.to_digit(10)) {
+                            ipv4_piece = match ipv4_piece {
+                                None => Some(number as u16),
+                                Some(0) => {
+                                    report_validation_error();
+                                    return None;
+                                }
+                                Some(piece) => Some(piece * 10 + number as u16),
+                            };
+                            if ipv4_piece > Some(255) {
+                                report_validation_error();
+                                return None;
+                            }
+                            pointer += 1;
+                        }
+
+                        let Some(ipv4_piece) = ipv4_piece else {
+                            report_validation_error();
+                            return None;
+                        };
+                        address[piece_index] = address[piece_index] * 0x100 + ipv4_piece;
+                        numbers_seen += 1;
+                        if numbers_seen == 2 || numbers_seen == 4 {
+                            piece_index += 1;
+                        }
+                    }
+
+                    if numbers_seen != 4 {
+                        report_validation_error();
+                        return None;
+                    }
+                    break;
+                }
+                Some(':') => {
+                    pointer += 1;
+                    if c(pointer).is_none() {
+                        report_validation_error();
+                        return None;
+                    }
+                }
+                Some(_) => {
+                    report_validation_error();
+                    return None;
+                }
+                None => {}
+            }
+
+            address[piece_index] = value;
+            piece_index += 1;
+        }
+
+        match compress {
+            Some(compress) => {
+                let mut swaps = piece_index - compress;
+                piece_index = 7;
+                while piece_index != 0 && swaps > 0 {
+                    address.swap(piece_index, compress + swaps - 1);
+                    piece_index -= 1;
+                    swaps -= 1;
+                }
+            }
+            None if piece_index != 8 => {
+                report_validation_error();
+                return None;
+            }
+            None => {}
+        }
+
+        Some(address)
     }
 
-    fn parse_opaque_host(input: &str) -> Option<String> {
+    fn parse_opaque_host(input: &str) -> Option<Host> {
         if contains_forbidden_host_code_point(input) {
             report_validation_error();
             return None;
@@ -39,10 +265,142 @@ impl HostParser {
 
         // TODO: If input contains a U+0025 (%) and the two code points following it are not ASCII hex digits, validation error.
 
-        return Some(URLPercentEncode::encode(
+        if input.is_empty() {
+            return Some(Host::Empty);
+        }
+
+        Some(Host::Opaque(URLPercentEncode::encode(
             input.as_bytes(),
             PercentEncodeSet::C0Control,
             false,
-        ));
+        )))
+    }
+}
+
+/// https://url.spec.whatwg.org/#ends-in-a-number-checker
+fn ends_in_a_number(input: &str) -> bool {
+    let mut parts: Vec<&str> = input.split('.').collect();
+    if parts.last() == Some(&"") {
+        if parts.len() == 1 {
+            return false;
+        }
+        parts.pop();
+    }
+
+    match parts.last() {
+        Some(last) if !last.is_empty() && last.chars().all(|c| c.is_ascii_digit()) => true,
+        Some(last) => parse_ipv4_number(last).is_some(),
+        None => false,
+    }
+}
+
+/// Parse a part of an IPv4 address, which may be written in decimal, in octal
+/// with a leading `0` or in hexadecimal with a leading `0x`.
+/// https://url.spec.whatwg.org/#ipv4-number-parser
+fn parse_ipv4_number(input: &str) -> Option<u64> {
+    if input.is_empty() {
+        return None;
+    }
+
+    let (input, radix) = if let Some(hex) = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+    {
+        (hex, 16)
+    } else if input.len() > 1 && input.starts_with('0') {
+        (&input[1..], 8)
+    } else {
+        (input, 10)
+    };
+
+    if input.is_empty() {
+        return Some(0);
+    }
+    if !input.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    // Numbers too large for a u64 are too large for an address as well.
+    u64::from_str_radix(input, radix).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_domain() {
+        let cases = [
+            ("EXAMPLE.com", Some("example.com")),
+            ("b%C3%BCcher.de", Some("xn--bcher-kva.de")),
+            ("Bücher。de", Some("xn--bcher-kva.de")),
+            ("xn--bcher-kva.de", Some("xn--bcher-kva.de")),
+            ("xn--.de", None),
+            ("exa%25mple.com", None),
+            ("a<b", None),
+        ];
+
+        for (input, expected) in cases {
+            let expected = expected.map(|domain| Host::Domain(domain.to_string()));
+            assert_eq!(HostParser::parse(input, false), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parse_ipv4() {
+        let cases = [
+            ("127.0.0.1", Some([127, 0, 0, 1])),
+            ("0x7f.1", Some([127, 0, 0, 1])),
+            ("0177.0.0.01", Some([127, 0, 0, 1])),
+            ("2130706433", Some([127, 0, 0, 1])),
+            ("192.168.0.1.", Some([192, 168, 0, 1])),
+            ("256.0.0.1", None),
+            ("1.2.3.4.5", None),
+            ("0x100000000", None),
+            ("1.09", None),
+        ];
+
+        for (input, expected) in cases {
+            let expected = expected.map(|octets| Host::Ipv4(u32::from_be_bytes(octets)));
+            assert_eq!(HostParser::parse(input, false), expected, "{}", input);
+        }
+        assert_eq!(
+            HostParser::parse("example.1a", false),
+            Some(Host::Domain("example.1a".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_ipv6() {
+        let cases = [
+            ("[::1]", Some("[::1]")),
+            ("[0:0:0:0:0:0:0:1]", Some("[::1]")),
+            (
+                "[2001:DB8::8:800:200C:417A]",
+                Some("[2001:db8::8:800:200c:417a]"),
+            ),
+            ("[1:0:0:2:0:0:0:3]", Some("[1:0:0:2::3]")),
+            ("[::ffff:192.168.0.1]", Some("[::ffff:c0a8:1]")),
+            ("[1:2:3:4:5:6:7:8]", Some("[1:2:3:4:5:6:7:8]")),
+            ("[::1", None),
+            ("[1::2::3]", None),
+            ("[1:2:3:4:5:6:7:8:9]", None),
+            ("[::ffff:192.168.0]", None),
+            ("[::ffff:192.168.0.01]", None),
+        ];
+
+        for (input, expected) in cases {
+            let host = HostParser::parse(input, false).map(|host| host.to_string());
+            assert_eq!(host.as_deref(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn parse_opaque() {
+        assert_eq!(
+            HostParser::parse("EX%41mple", true),
+            Some(Host::Opaque("EX%41mple".to_string()))
+        );
+        assert_eq!(HostParser::parse("", true), Some(Host::Empty));
+        assert_eq!(HostParser::parse("a b", true), None);
     }
 }
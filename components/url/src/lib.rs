@@ -1,6 +1,10 @@
+pub mod data_url;
 mod encode;
 mod helper;
 mod host_parser;
 pub mod parser;
+mod punycode;
+pub mod search_params;
 mod url;
+pub use crate::encode::{percent_decode, percent_decode_str};
 pub use crate::url::*;
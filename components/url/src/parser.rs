@@ -3,11 +3,11 @@ use super::Url;
 use crate::{
     encode::{PercentEncodeSet, URLPercentEncode},
     helper::{
-        is_c0_control_or_space, is_double_dot_path_segment, is_single_dot_path_segment,
-        is_start_with_two_hex, is_start_with_windows_drive_letter, is_url_c,
-        is_window_drive_letter, SPECIAL_SCHEME_PORTS,
+        is_c0_control_or_space, is_double_dot_path_segment, is_normalized_window_drive_letter,
+        is_single_dot_path_segment, is_start_with_two_hex, is_start_with_windows_drive_letter,
+        is_url_c, is_window_drive_letter, SPECIAL_SCHEMES, SPECIAL_SCHEME_PORTS,
     },
-    UrlPath,
+    Host, UrlPath,
 };
 use regex::Regex;
 use std::iter::FromIterator;
@@ -43,26 +43,47 @@ fn report_validation_error() {
     // TODO here....
 }
 
+fn default_port(scheme: &str) -> Option<u16> {
+    SPECIAL_SCHEME_PORTS
+        .iter()
+        .find(|(special_scheme, _)| *special_scheme == scheme)
+        .and_then(|(_, port)| *port)
+}
+
 impl URLParser {
     pub fn parse(raw_input: &str, base: Option<Url>) -> Option<Url> {
         URLParser::simple_parse(raw_input, base, None, None)
     }
 
+    /// Parse `input` into an existing URL, starting from `state` instead of the
+    /// scheme. This is how the setters of the URL API update a URL.
+    pub(crate) fn parse_with_state_override(
+        input: &str,
+        url: Url,
+        state: URLParseState,
+    ) -> Option<Url> {
+        URLParser::simple_parse(input, None, Some(url), Some(state))
+    }
+
     fn simple_parse(
         p_input: &str,
         base: Option<Url>,
         p_url: Option<Url>,
         p_state: Option<URLParseState>,
     ) -> Option<Url> {
+        // Setters parse their value into an existing URL, which is not trimmed.
+        let input = if p_url.is_none() {
+            p_input.trim_matches(|c| is_c0_control_or_space(c as u32))
+        } else {
+            p_input
+        };
+
         let mut url = p_url.unwrap_or(Url::new());
         let mut state = p_state.clone().unwrap_or(URLParseState::SchemeStart);
 
         let tab_newline_re = Regex::new(r"[\t\r\n]").unwrap();
 
-        let mut input = p_input
-            .trim_start_matches(|c| is_c0_control_or_space(c as u32))
-            .to_string();
-        input = tab_newline_re.replace_all(&input, "").to_string();
+        let input = tab_newline_re.replace_all(input, "").to_string();
 
         let mut buffer = String::new();
         let mut at_sign_seen = false;
@@ -101,13 +122,30 @@ impl URLParser {
                     }
                 }
                 URLParseState::Scheme => {
-                    if c != eof && (c.is_alphanumeric() || c == '+' || c == '-' || c == '.') {
+                    if c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.' {
                         buffer.push(c.to_ascii_lowercase());
                     } else if c == ':' {
-                        // TODO: Skipped step 1, 3
+                        if p_state.is_some() {
+                            let is_special_buffer = SPECIAL_SCHEMES.contains(&buffer.as_str());
+                            if url.is_special() != is_special_buffer
+                                || (url.includes_credentials() || url.port.is_some())
+                                    && buffer == "file"
+                                || url.scheme == "file" && url.host == Some(Host::Empty)
+                            {
+                                return Some(url);
+                            }
+                        }
+
                         url.scheme = buffer.clone();
                         buffer.clear();
 
+                        if p_state.is_some() {
+                            if url.port.is_some() && url.port == default_port(&url.scheme) {
+                                url.port = None;
+                            }
+                            return Some(url);
+                        }
+
                         if url.scheme == "file" {
                             if !remaining(pointer).starts_with("//") {
                                 report_validation_error();
@@ -184,6 +222,8 @@ impl URLParser {
                         report_validation_error();
                         state = URLParseState::RelativeSlash;
                     } else {
+                        url.username = base_clone.username;
+                        url.password = base_clone.password;
                         url.host = base_clone.host;
                         url.port = base_clone.port;
                         url.path = base_clone.path;
@@ -205,14 +245,16 @@ impl URLParser {
                 }
                 URLParseState::RelativeSlash => {
                     let base_clone = base.clone().unwrap();
-                    if url.is_special() && c == '/' || c == '\\' {
+                    if url.is_special() && (c == '/' || c == '\\') {
                         if c == '\\' {
                             report_validation_error();
-                            state = URLParseState::SpecialAuthorityIgnoreSlashes;
                         }
+                        state = URLParseState::SpecialAuthorityIgnoreSlashes;
                     } else if c == '/' {
                         state = URLParseState::Authority;
                     } else {
+                        url.username = base_clone.username;
+                        url.password = base_clone.password;
                         url.host = base_clone.host;
                         url.port = base_clone.port;
                         state = URLParseState::Path;
@@ -250,7 +292,16 @@ impl URLParser {
                                 password_token_seen = true;
                                 continue;
                             }
-                            // TODO: skipped password & username processing
+                            let encoded = URLPercentEncode::encode(
+                                c.to_string().as_bytes(),
+                                PercentEncodeSet::UserInfo,
+                                false,
+                            );
+                            if password_token_seen {
+                                url.password.push_str(&encoded);
+                            } else {
+                                url.username.push_str(&encoded);
+                            }
                         }
 
                         buffer.clear();
@@ -261,7 +312,7 @@ impl URLParser {
                             report_validation_error();
                             return None;
                         }
-                        pointer -= buffer.len() + 1;
+                        pointer -= buffer.chars().count() + 1;
                         buffer.clear();
                         state = URLParseState::Host;
                     } else {
@@ -277,8 +328,11 @@ impl URLParser {
                             report_validation_error();
                             return None;
                         }
+                        if matches!(p_state, Some(URLParseState::Hostname)) {
+                            return Some(url);
+                        }
 
-                        let host = HostParser::parse(&buffer, true);
+                        let host = HostParser::parse(&buffer, !url.is_special());
 
                         if host.is_none() {
                             return None;
@@ -293,8 +347,13 @@ impl URLParser {
                         if url.is_special() && buffer.is_empty() {
                             report_validation_error();
                             return None;
+                        } else if p_state.is_some()
+                            && buffer.is_empty()
+                            && (url.includes_credentials() || url.port.is_some())
+                        {
+                            return Some(url);
                         }
-                        let host = HostParser::parse(&buffer, true);
+                        let host = HostParser::parse(&buffer, !url.is_special());
 
                         if host.is_none() {
                             return None;
@@ -302,6 +361,9 @@ impl URLParser {
 
                         url.host = host;
                         buffer.clear();
+                        if p_state.is_some() {
+                            return Some(url);
+                        }
                         state = URLParseState::PathStart;
                         continue;
                     } else {
@@ -322,21 +384,22 @@ impl URLParser {
                     {
                         if !buffer.is_empty() {
                             match u16::from_str_radix(&buffer, 10) {
-                                Ok(port) => {
-                                    if SPECIAL_SCHEME_PORTS.iter().any(|(scheme, s_port)| {
-                                        *scheme == url.scheme && *s_port == Some(port)
-                                    }) {
-                                        url.port = None;
-                                    } else {
-                                        url.port = Some(port);
-                                    }
+                                Ok(port) if default_port(&url.scheme) == Some(port) => {
+                                    url.port = None;
                                 }
+                                Ok(port) => url.port = Some(port),
                                 _ => {
                                     report_validation_error();
                                     return None;
                                 }
                             }
                             buffer.clear();
+                            if p_state.is_some() {
+                                return Some(url);
+                            }
+                        }
+                        if p_state.is_some() {
+                            return None;
                         }
                         state = URLParseState::PathStart;
                         continue;
@@ -347,7 +410,7 @@ impl URLParser {
                 }
                 URLParseState::File => {
                     url.scheme = String::from("file");
-                    url.host = Some(String::new());
+                    url.host = Some(Host::Empty);
                     if c == '/' || c == '\\' {
                         if c == '\\' {
                             report_validation_error();
@@ -375,10 +438,10 @@ impl URLParser {
                             }
                             state = URLParseState::Path;
                             continue;
-                        } else {
-                            state = URLParseState::Path;
-                            continue;
                         }
+                    } else {
+                        state = URLParseState::Path;
+                        continue;
                     }
                 }
                 URLParseState::FileSlash => {
@@ -388,9 +451,17 @@ impl URLParser {
                         }
                         state = URLParseState::FileHost;
                     } else {
-                        if matches!(&base, Some(b) if b.scheme == "file") {
-                            let base_clone = base.clone().unwrap();
-                            url.host = base_clone.host;
+                        if let Some(base) = base.as_ref().filter(|b| b.scheme == "file") {
+                            url.host = base.host.clone();
+                            if let UrlPath::List(base_path) = &base.path {
+                                if !is_start_with_windows_drive_letter(&codepoint_substr(pointer))
+                                    && base_path.first().is_some_and(|first| {
+                                        is_normalized_window_drive_letter(first)
+                                    })
+                                {
+                                    url.path.append(&base_path[0]);
+                                }
+                            }
                         }
                         state = URLParseState::Path;
                         continue;
@@ -398,26 +469,31 @@ impl URLParser {
                 }
                 URLParseState::FileHost => {
                     if c == eof || c == '/' || c == '\\' || c == '?' || c == '#' {
-                        if p_state.is_none() {
-                            if is_window_drive_letter(&buffer) {
-                                report_validation_error();
-                            }
+                        if p_state.is_none() && is_window_drive_letter(&buffer) {
+                            // The drive letter is kept in the buffer for the path state.
+                            report_validation_error();
                             state = URLParseState::Path;
                         } else if buffer.is_empty() {
-                            url.host = Some(String::new());
+                            url.host = Some(Host::Empty);
+                            if p_state.is_some() {
+                                return Some(url);
+                            }
                             state = URLParseState::PathStart;
                         } else {
-                            let mut host = HostParser::parse(&buffer, true);
+                            let mut host = HostParser::parse(&buffer, false);
 
                             if host.is_none() {
                                 return None;
                             }
 
-                            if host == Some("localhost".to_string()) {
-                                host = Some(String::new());
+                            if host == Some(Host::Domain("localhost".to_string())) {
+                                host = Some(Host::Empty);
                             }
 
                             url.host = host;
+                            if p_state.is_some() {
+                                return Some(url);
+                            }
                             buffer.clear();
                             state = URLParseState::PathStart;
                         }
@@ -435,10 +511,10 @@ impl URLParser {
                         if c != '/' && c != '\\' {
                             continue;
                         }
-                    } else if c == '?' {
+                    } else if p_state.is_none() && c == '?' {
                         url.query = Some(String::new());
                         state = URLParseState::Query;
-                    } else if c == '#' {
+                    } else if p_state.is_none() && c == '#' {
                         url.fragment = Some(String::new());
                         state = URLParseState::Fragment;
                     } else if c != eof {
@@ -458,8 +534,7 @@ impl URLParser {
                     if c == eof
                         || c == '/'
                         || (url.is_special() && c == '\\')
-                        || c == '?'
-                        || c == '#'
+                        || (p_state.is_none() && (c == '?' || c == '#'))
                     {
                         if url.is_special() && c == '\\' {
                             report_validation_error();
@@ -500,7 +575,7 @@ impl URLParser {
                             report_validation_error();
                         }
                         buffer.push_str(&URLPercentEncode::encode(
-                            &[c as u8],
+                            c.to_string().as_bytes(),
                             PercentEncodeSet::Path,
                             false,
                         ));
@@ -524,7 +599,7 @@ impl URLParser {
 
                         if c != eof {
                             url.path.append(&URLPercentEncode::encode(
-                                &[c as u8],
+                                c.to_string().as_bytes(),
                                 PercentEncodeSet::C0Control,
                                 false,
                             ));
@@ -576,7 +651,7 @@ impl URLParser {
 
                         if let Some(fragment) = &mut url.fragment {
                             fragment.push_str(&URLPercentEncode::encode(
-                                &[c as u8],
+                                c.to_string().as_bytes(),
                                 PercentEncodeSet::Fragment,
                                 false,
                             ));
@@ -607,9 +682,9 @@ mod tests {
         let url = URLParser::parse(input_url, None).unwrap();
 
         assert_eq!(url.scheme, "http");
-        assert_eq!(url.host, Some("google.com".to_string()));
+        assert_eq!(url.host, Some(Host::Domain("google.com".to_string())));
         assert_eq!(url.port, None);
-        assert_eq!(url.path, "index.php");
+        assert_eq!(url.path, "/index.php");
     }
 
     #[test]
@@ -619,9 +694,9 @@ mod tests {
         let url = URLParser::parse(input_url, None).unwrap();
 
         assert_eq!(url.scheme, "http");
-        assert_eq!(url.host, Some("google.com".to_string()));
+        assert_eq!(url.host, Some(Host::Domain("google.com".to_string())));
         assert_eq!(url.port, None);
-        assert_eq!(url.path, "");
+        assert_eq!(url.path, "/");
     }
 
     #[test]
@@ -631,9 +706,9 @@ mod tests {
         let url = URLParser::parse(input_url, None).unwrap();
 
         assert_eq!(url.scheme, "https");
-        assert_eq!(url.host, Some("google.com".to_string()));
+        assert_eq!(url.host, Some(Host::Domain("google.com".to_string())));
         assert_eq!(url.port, None);
-        assert_eq!(url.path, "");
+        assert_eq!(url.path, "/");
     }
 
     #[test]
@@ -643,9 +718,9 @@ mod tests {
         let url = URLParser::parse(input_url, None).unwrap();
 
         assert_eq!(url.scheme, "https");
-        assert_eq!(url.host, Some("google.com".to_string()));
+        assert_eq!(url.host, Some(Host::Domain("google.com".to_string())));
         assert_eq!(url.port, Some(1242));
-        assert_eq!(url.path, "");
+        assert_eq!(url.path, "/");
     }
 
     #[test]
@@ -675,8 +750,8 @@ mod tests {
         let url = URLParser::parse(input_url, base_url).unwrap();
 
         assert_eq!(url.scheme, "http");
-        assert_eq!(url.host, Some("google.com".to_string()));
-        assert_eq!(url.path, "index.html");
+        assert_eq!(url.host, Some(Host::Domain("google.com".to_string())));
+        assert_eq!(url.path, "/index.html");
     }
 
     #[test]
@@ -697,7 +772,20 @@ mod tests {
 
         let url = URLParser::parse(input_url, base_url).unwrap();
 
-        assert_eq!(url.path, "_folder/index.html");
+        assert_eq!(url.path, "/_folder/index.html");
+    }
+
+    #[test]
+    fn data_scheme() {
+        let input_url = "data:text/html,<p>é</p>#top";
+
+        let url = URLParser::parse(input_url, None).unwrap();
+
+        assert_eq!(url.scheme, "data");
+        assert_eq!(url.host, None);
+        assert_eq!(url.path, "text/html,<p>%C3%A9</p>");
+        assert_eq!(url.fragment, Some("top".to_string()));
+        assert_eq!(url.as_str(), "data:text/html,<p>%C3%A9</p>#top");
     }
 
     #[test]
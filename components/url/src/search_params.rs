@@ -0,0 +1,195 @@
+use std::fmt::Display;
+
+use crate::encode::{percent_decode, PercentEncodeSet, URLPercentEncode};
+
+/// The name-value pairs of a query, e.g. `q=moon&page=2`, with the API of
+/// `URLSearchParams`.
+/// https://url.spec.whatwg.org/#interface-urlsearchparams
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchParams {
+    list: Vec<(String, String)>,
+}
+
+impl SearchParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a query, with or without its leading `?`.
+    pub fn parse(input: &str) -> Self {
+        let input = input.strip_prefix('?').unwrap_or(input);
+        Self {
+            list: parse_form_urlencoded(input.as_bytes()),
+        }
+    }
+
+    /// The value of the first pair with the given name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.list
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The values of all the pairs with the given name, in order.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.list
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.list.iter().any(|(key, _)| key == name)
+    }
+
+    pub fn append(&mut self, name: &str, value: &str) {
+        self.list.push((name.to_string(), value.to_string()));
+    }
+
+    /// Replace the value of the first pair with the given name and remove the
+    /// others, or append a pair if there is none.
+    pub fn set(&mut self, name: &str, value: &str) {
+        let mut found = false;
+        self.list.retain_mut(|(key, old_value)| {
+            if key != name {
+                return true;
+            }
+            if found {
+                return false;
+            }
+            found = true;
+            *old_value = value.to_string();
+            true
+        });
+
+        if !found {
+            self.append(name, value);
+        }
+    }
+
+    pub fn delete(&mut self, name: &str) {
+        self.list.retain(|(key, _)| key != name);
+    }
+
+    /// Sort the pairs by name, comparing UTF-16 code units like JavaScript does.
+    /// Pairs with the same name keep their relative order.
+    pub fn sort(&mut self) {
+        self.list
+            .sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.list
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+impl Display for SearchParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", serialize_form_urlencoded(self.iter()))
+    }
+}
+
+/// https://url.spec.whatwg.org/#concept-urlencoded-parser
+pub fn parse_form_urlencoded(input: &[u8]) -> Vec<(String, String)> {
+    input
+        .split(|&byte| byte == b'&')
+        .filter(|sequence| !sequence.is_empty())
+        .map(|sequence| {
+            let (name, value) = match sequence.iter().position(|&byte| byte == b'=') {
+                Some(index) => (&sequence[..index], &sequence[index + 1..]),
+                None => (sequence, &[][..]),
+            };
+            (decode_form_component(name), decode_form_component(value))
+        })
+        .collect()
+}
+
+/// https://url.spec.whatwg.org/#concept-urlencoded-serializer
+pub fn serialize_form_urlencoded<'a>(
+    pairs: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> String {
+    pairs
+        .into_iter()
+        .map(|(name, value)| {
+            format!(
+                "{}={}",
+                encode_form_component(name),
+                encode_form_component(value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn decode_form_component(input: &[u8]) -> String {
+    let input: Vec<u8> = input
+        .iter()
+        .map(|&byte| if byte == b'+' { b' ' } else { byte })
+        .collect();
+    String::from_utf8_lossy(&percent_decode(&input)).into_owned()
+}
+
+fn encode_form_component(input: &str) -> String {
+    URLPercentEncode::encode(input.as_bytes(), PercentEncodeSet::FormUrlencoded, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_serialize() {
+        let params = SearchParams::parse("?q=moon+browser&&lang=en&q=caf%C3%A9&flag&=x");
+
+        assert_eq!(params.get("q"), Some("moon browser"));
+        assert_eq!(params.get_all("q"), vec!["moon browser", "café"]);
+        assert_eq!(params.get("flag"), Some(""));
+        assert_eq!(params.get(""), Some("x"));
+        assert_eq!(params.get("page"), None);
+        assert_eq!(
+            params.to_string(),
+            "q=moon+browser&lang=en&q=caf%C3%A9&flag=&=x"
+        );
+        assert_eq!(
+            serialize_form_urlencoded([("a b", "1+1=2&*-._~!")]),
+            "a+b=1%2B1%3D2%26*-._%7E%21"
+        );
+    }
+
+    #[test]
+    fn modify() {
+        let mut params = SearchParams::parse("b=1&a=2&b=3&c=4&b=5");
+
+        params.set("b", "6");
+        assert_eq!(params.to_string(), "b=6&a=2&c=4");
+
+        params.append("a", "7");
+        params.set("d", "8");
+        params.delete("c");
+        assert_eq!(params.to_string(), "b=6&a=2&a=7&d=8");
+        assert!(params.has("d"));
+        assert!(!params.has("c"));
+
+        params.sort();
+        assert_eq!(params.to_string(), "a=2&a=7&b=6&d=8");
+
+        let mut params = SearchParams::parse("\u{FB03}=1&\u{1F600}=2");
+        params.sort();
+        assert_eq!(
+            params.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec!["\u{1F600}", "\u{FB03}"]
+        );
+    }
+}
@@ -1,4 +1,6 @@
 use flume::{Receiver, Sender};
+use loader::resource_loop::cache::HttpCache;
+use net::cookie::CookieJar;
 use shared::primitive::Size;
 
 use render::{InputEvent, OutputEvent, RenderEngine};
@@ -10,7 +12,7 @@ pub struct RenderClient {
 }
 
 impl RenderClient {
-    pub fn new() -> Self {
+    pub fn new(cookie_jar: CookieJar) -> Self {
         let (render_input_tx, render_input_rx) = flume::unbounded();
         let (render_output_tx, render_output_rx) = flume::unbounded();
 
@@ -20,7 +22,8 @@ impl RenderClient {
         let _ = std::thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
-                let render_engine = RenderEngine::new(Size::new(1., 1.)).await;
+                let cache = Some(HttpCache::default_location());
+                let render_engine = RenderEngine::new(Size::new(1., 1.), cookie_jar, cache).await;
 
                 ready_tx.send(()).unwrap();
 
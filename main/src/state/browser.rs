@@ -1,6 +1,7 @@
 use std::path::Path;
 
 use flume::{Receiver, Sender};
+use net::cookie::CookieJar;
 use shared::primitive::{Point, Size};
 use url::{parser::URLParser, Url};
 
@@ -88,6 +89,8 @@ impl BrowserHandler {
 
 pub struct Browser {
     home_url: Url,
+    /// Shared by every tab and persisted in the profile directory.
+    cookie_jar: CookieJar,
     tab_handlers: Vec<TabHandler>,
     active_tab_index: usize,
     update_channel: (Sender<BrowserAction>, Receiver<BrowserAction>),
@@ -108,16 +111,22 @@ impl Browser {
         )
         .unwrap();
 
-        let initial_tab = BrowserTab::new(home_url.clone());
-        let initial_tab_handler = start_tab(initial_tab);
-
-        Self {
-            home_url,
-            tab_handlers: vec![initial_tab_handler],
+        let mut browser = Self {
+            home_url: home_url.clone(),
+            cookie_jar: CookieJar::open(CookieJar::default_location()),
+            tab_handlers: Vec::new(),
             active_tab_index: 0,
             update_channel: flume::unbounded(),
             tab_event_channel: flume::unbounded(),
-        }
+        };
+        browser.open_tab(home_url);
+        browser
+    }
+
+    /// Start a tab on the given URL, with a clone of the cookie jar of the browser.
+    fn open_tab(&mut self, url: Url) {
+        let tab = BrowserTab::new(url, self.cookie_jar.clone());
+        self.tab_handlers.push(start_tab(tab));
     }
 
     pub fn handler(&self) -> BrowserHandler {
@@ -2,6 +2,7 @@ use std::sync::{Arc, Mutex};
 
 use crate::render_client::RenderClient;
 use flume::{Receiver, Selector, Sender};
+use net::cookie::CookieJar;
 use render::OutputEvent;
 use shared::primitive::{Point, Size};
 use url::Url;
@@ -75,8 +76,8 @@ pub struct BrowserTab {
 }
 
 impl BrowserTab {
-    pub fn new(url: Url) -> Self {
-        let client = RenderClient::new();
+    pub fn new(url: Url, cookie_jar: CookieJar) -> Self {
+        let client = RenderClient::new(cookie_jar);
         client.wait_till_ready();
 
         let info = TabInfo {
@@ -144,7 +145,9 @@ impl BrowserTab {
 
     fn handle_render_engine_event(&self, event: OutputEvent) -> anyhow::Result<()> {
         match event {
-            OutputEvent::FrameRendered(frame) => self.emit_event(TabEvent::FrameReceived(frame))?,
+            OutputEvent::FrameRendered(frame, _) => {
+                self.emit_event(TabEvent::FrameReceived(frame))?
+            }
             OutputEvent::TitleChanged(title) => self.emit_event(TabEvent::TitleChanged(title))?,
             OutputEvent::URLChanged(url) => self.emit_event(TabEvent::URLChanged(url))?,
             OutputEvent::LoadingStarted => self.emit_event(TabEvent::LoadingStart)?,
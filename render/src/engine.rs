@@ -1,10 +1,16 @@
 use super::page::Page;
-use flume::{Receiver, Sender};
+use std::{collections::VecDeque, time::Duration};
+
+use flume::{Receiver, RecvTimeoutError, Sender};
 use gfx::Bitmap;
-use loader::resource_loop::{request::LoadRequest, ResourceLoop};
+use loader::resource_loop::{cache::HttpCache, request::LoadRequest, ResourceLoop};
+use net::cookie::CookieJar;
 use shared::primitive::{Point, Size};
 use url::Url;
 
+/// How often the engine checks for stylesheets that arrived after the page was painted.
+const STYLESHEET_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 pub enum InputEvent {
     ViewportResize(Size),
     Scroll(f32),
@@ -15,26 +21,44 @@ pub enum InputEvent {
 }
 
 pub enum OutputEvent {
-    FrameRendered(Bitmap),
+    FrameRendered(Bitmap, Size),
     TitleChanged(String),
     URLChanged(Url),
     LoadingStarted,
     LoadingFinished,
 }
 
+impl InputEvent {
+    fn is_navigation(&self) -> bool {
+        matches!(
+            self,
+            InputEvent::LoadHTML { .. } | InputEvent::LoadRawURL(_) | InputEvent::Reload
+        )
+    }
+}
+
 pub struct RenderEngine {
     page: Page,
     resource_loop_tx: Sender<LoadRequest>,
+    /// Events received while a navigation was loading, handled once it's done.
+    deferred_events: VecDeque<InputEvent>,
 }
 
 impl RenderEngine {
-    pub async fn new(viewport: Size) -> RenderEngine {
+    pub async fn new(
+        viewport: Size,
+        cookie_jar: CookieJar,
+        cache: Option<HttpCache>,
+    ) -> RenderEngine {
         let page = Page::new(viewport).await;
-        let resource_loop = ResourceLoop::new();
+        let resource_loop = ResourceLoop::new()
+            .with_cookie_jar(cookie_jar)
+            .with_cache(cache);
         let resource_loop_tx = resource_loop.start_loop();
         Self {
             page,
             resource_loop_tx,
+            deferred_events: VecDeque::new(),
         }
     }
 
@@ -44,14 +68,40 @@ impl RenderEngine {
         event_emitter: Sender<OutputEvent>,
     ) -> anyhow::Result<()> {
         loop {
-            let event = event_receiver.recv()?;
-            self.handle_event(event, &event_emitter).await?;
+            if let Some(event) = self.deferred_events.pop_front() {
+                self.handle_event(event, &event_receiver, &event_emitter)
+                    .await?;
+                continue;
+            }
+
+            if !self.page.has_pending_stylesheets() {
+                let event = event_receiver.recv()?;
+                self.handle_event(event, &event_receiver, &event_emitter)
+                    .await?;
+                continue;
+            }
+
+            match event_receiver.recv_timeout(STYLESHEET_POLL_INTERVAL) {
+                Ok(event) => {
+                    self.handle_event(event, &event_receiver, &event_emitter)
+                        .await?
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(RecvTimeoutError::Disconnected.into())
+                }
+            }
+
+            if self.page.apply_loaded_stylesheets().await {
+                self.emit_new_frame(&event_emitter)?;
+            }
         }
     }
 
     async fn handle_event(
         &mut self,
         event: InputEvent,
+        event_receiver: &Receiver<InputEvent>,
         event_emitter: &Sender<OutputEvent>,
     ) -> anyhow::Result<()> {
         match event {
@@ -67,35 +117,51 @@ impl RenderEngine {
                 self.page.handle_mouse_move(coord).await;
                 self.emit_new_frame(event_emitter)?;
             }
-            InputEvent::LoadHTML { html, base_url } => {
-                self.emit_loading_started(event_emitter)?;
-                self.page
-                    .load_html(html, base_url, self.resource_loop_tx.clone())
-                    .await;
-                self.emit_loading_finished(event_emitter)?;
-                self.emit_new_frame(event_emitter)?;
-                self.emit_new_title(event_emitter)?;
-                self.emit_new_url(event_emitter)?;
-            }
-            InputEvent::LoadRawURL(url) => {
-                self.emit_loading_started(event_emitter)?;
-                self.page
-                    .load_raw_url(url, self.resource_loop_tx.clone())
-                    .await;
-                self.emit_loading_finished(event_emitter)?;
-                self.emit_new_frame(event_emitter)?;
-                self.emit_new_title(event_emitter)?;
-                self.emit_new_url(event_emitter)?;
+            navigation => {
+                self.navigate(navigation, event_receiver, event_emitter)
+                    .await?
             }
-            InputEvent::Reload => {
-                self.emit_loading_started(event_emitter)?;
-                self.page.reload(self.resource_loop_tx.clone()).await;
-                self.emit_loading_finished(event_emitter)?;
-                self.emit_new_frame(event_emitter)?;
-                self.emit_new_title(event_emitter)?;
-                self.emit_new_url(event_emitter)?;
+        }
+        Ok(())
+    }
+
+    /// Load a document while listening for newer input. A newer navigation aborts
+    /// the one in progress and replaces it, other events wait until the page is
+    /// loaded.
+    async fn navigate(
+        &mut self,
+        navigation: InputEvent,
+        event_receiver: &Receiver<InputEvent>,
+        event_emitter: &Sender<OutputEvent>,
+    ) -> anyhow::Result<()> {
+        self.emit_loading_started(event_emitter)?;
+
+        let mut next_navigation = Some(navigation);
+        while let Some(navigation) = next_navigation.take() {
+            let abort_handle = self.page.next_navigation_abort_handle();
+            let load = load(&mut self.page, navigation, self.resource_loop_tx.clone());
+            tokio::pin!(load);
+
+            loop {
+                tokio::select! {
+                    _ = &mut load => break,
+                    event = event_receiver.recv_async(), if next_navigation.is_none() => {
+                        let event = event?;
+                        if event.is_navigation() {
+                            abort_handle.abort();
+                            next_navigation = Some(event);
+                        } else {
+                            self.deferred_events.push_back(event);
+                        }
+                    }
+                }
             }
         }
+
+        self.emit_loading_finished(event_emitter)?;
+        self.emit_new_frame(event_emitter)?;
+        self.emit_new_title(event_emitter)?;
+        self.emit_new_url(event_emitter)?;
         Ok(())
     }
 
@@ -123,8 +189,19 @@ impl RenderEngine {
 
     fn emit_new_frame(&self, event_emitter: &Sender<OutputEvent>) -> anyhow::Result<()> {
         if let Some(frame) = self.page.bitmap() {
-            event_emitter.send(OutputEvent::FrameRendered(frame.clone()))?;
+            event_emitter.send(OutputEvent::FrameRendered(frame.clone(), self.page.size()))?;
         }
         Ok(())
     }
 }
+
+async fn load(page: &mut Page, navigation: InputEvent, resource_loop_tx: Sender<LoadRequest>) {
+    match navigation {
+        InputEvent::LoadHTML { html, base_url } => {
+            page.load_html(html, base_url, resource_loop_tx).await
+        }
+        InputEvent::LoadRawURL(url) => page.load_raw_url(url, resource_loop_tx).await,
+        InputEvent::Reload => page.reload(resource_loop_tx).await,
+        _ => {}
+    }
+}
@@ -87,6 +87,18 @@ impl Frame {
         .await;
     }
 
+    /// Re-run style and layout, e.g. after a stylesheet finished loading.
+    pub async fn restyle(&mut self, pipeline: &mut Pipeline) {
+        self.render_frame(
+            pipeline,
+            PipelineRunOptions {
+                skip_style_calculation: false,
+                skip_layout_calculation: false,
+            },
+        )
+        .await;
+    }
+
     pub fn document(&self) -> Option<NodePtr> {
         self.document.clone()
     }
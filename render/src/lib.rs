@@ -2,5 +2,7 @@ mod engine;
 mod frame;
 pub mod page;
 mod pipeline;
+pub mod profile;
+pub mod style_tree;
 
 pub use engine::*;
@@ -1,3 +1,8 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use dom::{
     document::Document,
     node::{Node, NodeData, NodePtr},
@@ -7,24 +12,41 @@ use gfx::Bitmap;
 use loader::{
     document_loader::DocumentLoader,
     resource_loop::{
+        cache::CacheMode,
         error::LoadError,
-        request::{FetchListener, LoadRequest},
+        request::{AbortHandle, Destination, FetchListener, LoadRequest},
+        response::Response,
     },
 };
 use shared::{byte_string::ByteString, primitive::Size, tree_node::TreeNode};
 use style_types::{CSSLocation, CascadeOrigin, ContextualStyleSheet};
 use url::{parser::URLParser, Url};
 
-use crate::pipeline::Pipeline;
+use crate::{
+    pipeline::Pipeline,
+    profile::{count_nodes, Profile, ProfiledTokenizer, Stage},
+    style_tree::StyleTreeNode,
+};
 
 use super::frame::Frame;
 
 const USER_AGENT_STYLES: &str = include_str!("./html.css");
+const STYLESHEET_LOAD_TIMEOUT: Duration = Duration::from_secs(10);
+/// Deterministic renders wait longer for stylesheets, as a stylesheet that
+/// misses the first frame changes the output, but a hung load still can't
+/// block the render forever.
+pub const DETERMINISTIC_STYLESHEET_LOAD_TIMEOUT: Duration = Duration::from_secs(60);
 
 pub struct Page {
     url: Option<Url>,
     main_frame: Frame,
     pipeline: Pipeline,
+    profile: Profile,
+    stylesheet_timeout: Duration,
+    cache_mode: CacheMode,
+    /// Loader of the current navigation, used by the document & its subresources.
+    loader: Option<DocumentLoader>,
+    next_navigation: AbortHandle,
 }
 
 impl Page {
@@ -33,9 +55,31 @@ impl Page {
             url: None,
             main_frame: Frame::new(init_size),
             pipeline: Pipeline::new().await,
+            profile: Profile::default(),
+            stylesheet_timeout: STYLESHEET_LOAD_TIMEOUT,
+            cache_mode: CacheMode::Default,
+            loader: None,
+            next_navigation: AbortHandle::new(),
         }
     }
 
+    pub fn new_cpu(init_size: Size) -> Page {
+        Page {
+            url: None,
+            main_frame: Frame::new(init_size),
+            pipeline: Pipeline::new_cpu(),
+            profile: Profile::default(),
+            stylesheet_timeout: STYLESHEET_LOAD_TIMEOUT,
+            cache_mode: CacheMode::Default,
+            loader: None,
+            next_navigation: AbortHandle::new(),
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        self.main_frame.size()
+    }
+
     pub async fn resize(&mut self, size: Size) {
         self.main_frame.resize(size, &mut self.pipeline).await;
     }
@@ -56,31 +100,76 @@ impl Page {
         base_url: Url,
         resource_loop_tx: Sender<LoadRequest>,
     ) {
+        let loader = self.start_navigation(resource_loop_tx);
+        self.load_document(html, "utf-8", base_url, loader).await;
+    }
+
+    /// Load an encoded HTML document, e.g. read from a file, decoding it with
+    /// the encoding declared by its content.
+    pub async fn load_html_bytes(
+        &mut self,
+        bytes: &[u8],
+        base_url: Url,
+        resource_loop_tx: Sender<LoadRequest>,
+    ) {
+        let loader = self.start_navigation(resource_loop_tx);
+        let decoded = ByteString::from_html(bytes, None);
+        self.load_document(decoded.to_string(), decoded.charset(), base_url, loader)
+            .await;
+    }
+
+    /// Parse and render a document. Nothing is rendered if the navigation is
+    /// aborted while stylesheets are loading.
+    async fn load_document(
+        &mut self,
+        html: String,
+        charset: &str,
+        base_url: Url,
+        loader: DocumentLoader,
+    ) {
+        let load_start = Instant::now();
         let document = NodePtr(TreeNode::new(Node::new(
             NodeData::Document(Document::new()),
         )));
 
-        document
-            .as_document()
-            .set_loader(DocumentLoader::new(resource_loop_tx));
+        let abort_handle = loader.abort_handle();
+        document.as_document().set_loader(loader);
 
+        let css_start = Instant::now();
         let tokenizer = css::tokenizer::Tokenizer::new(USER_AGENT_STYLES.chars());
         let mut parser = css::parser::Parser::<css::tokenizer::token::Token>::new(tokenizer.run());
         let stylesheet = parser.parse_a_css_stylesheet();
         let stylesheet =
             ContextualStyleSheet::new(stylesheet, CascadeOrigin::UserAgent, CSSLocation::External);
         document.as_document().set_user_agent_stylesheet(stylesheet);
+        document
+            .as_document()
+            .add_stylesheet_parse_time(css_start.elapsed());
 
-        log::debug!("Base URL: {}", base_url);
-        document.as_document().set_base(Some(base_url));
+        log::debug!("Document URL: {}", base_url);
+        document.as_document().set_url(Some(base_url.clone()));
+        document.as_document().set_charset(charset);
 
-        let tokenizer = html::tokenizer::Tokenizer::new(html.chars());
-        let tree_builder = html::tree_builder::TreeBuilder::new(tokenizer, document);
+        let html_start = Instant::now();
+        let css_time_before_parsing = document.as_document().stylesheet_parse_time();
+        let mut tokenizer = ProfiledTokenizer::new(html::tokenizer::Tokenizer::new(html.chars()));
+        let tree_builder = html::tree_builder::TreeBuilder::new(&mut tokenizer, document);
         let document = tree_builder.run();
+        self.record_parsing_profile(&document, &tokenizer, html_start, css_time_before_parsing);
+
+        tokio::select! {
+            _ = self.wait_for_stylesheets(&document) => {}
+            _ = abort_handle.aborted() => {
+                log::debug!("Navigation to {} aborted", base_url);
+                return;
+            }
+        }
+        self.record_css_profile(&document);
 
         self.main_frame
             .set_document(document, &mut self.pipeline)
             .await;
+        self.profile.load_html = load_start.elapsed();
     }
 
     pub async fn load_raw_url(&mut self, url: String, resource_loop_tx: Sender<LoadRequest>) {
@@ -97,11 +186,33 @@ impl Page {
     }
 
     pub async fn load_url(&mut self, url: Url, resource_loop_tx: Sender<LoadRequest>) {
+        let loader = self.start_navigation(resource_loop_tx);
         self.url = Some(url.clone());
-        match self.fetch_html(DocumentLoader::new(resource_loop_tx.clone()), url.clone()) {
-            Ok(html) => {
-                self.load_html(html, url, resource_loop_tx).await;
+        match self.fetch_html(&loader, url.clone()).await {
+            Ok(response) if !response.is_success() => {
+                self.show_error(
+                    &format!("{} {}", response.status, response.status_text),
+                    &format!("The server returned an error for {}.", response.url),
+                )
+                .await;
             }
+            Ok(response) => {
+                let decoded = ByteString::from_html(&response.body, response.charset().as_deref());
+                let html = decoded.to_string();
+
+                if url.scheme == "view-source" {
+                    let html = format!("<pre>{}</pre>", html_escape::encode_text(&html));
+                    self.load_document(html, decoded.charset(), url, loader)
+                        .await;
+                    return;
+                }
+
+                // Relative URLs of a redirected document resolve against its final URL.
+                self.url = Some(response.url.clone());
+                self.load_document(html, decoded.charset(), response.url, loader)
+                    .await;
+            }
+            Err(LoadError::Aborted) => log::debug!("Navigation to {} aborted", url),
             Err(e) => {
                 self.show_error("Oh no!", &format!("Error while loading page: {:?}", e))
                     .await;
@@ -109,16 +220,83 @@ impl Page {
         }
     }
 
+    /// Load the current URL again. Cached resources are revalidated with the
+    /// server so that unchanged ones are not downloaded again.
     pub async fn reload(&mut self, resource_loop_tx: Sender<LoadRequest>) {
-        if let Some(url) = &self.url {
-            self.load_url(url.clone(), resource_loop_tx).await;
+        if let Some(url) = self.url.clone() {
+            self.cache_mode = CacheMode::Revalidate;
+            self.load_url(url, resource_loop_tx).await;
+            self.cache_mode = CacheMode::Default;
+        }
+    }
+
+    /// How long `load_html` waits for external stylesheets before painting the
+    /// first frame.
+    pub fn set_stylesheet_timeout(&mut self, timeout: Duration) {
+        self.stylesheet_timeout = timeout;
+    }
+
+    pub fn has_pending_stylesheets(&self) -> bool {
+        self.main_frame
+            .document()
+            .map(|document| document.as_document().pending_stylesheets() > 0)
+            .unwrap_or(false)
+    }
+
+    /// Re-run style and layout if stylesheets finished loading after the page was
+    /// painted. Returns `true` if a new frame was rendered.
+    pub async fn apply_loaded_stylesheets(&mut self) -> bool {
+        let document = match self.main_frame.document() {
+            Some(document) => document,
+            None => return false,
+        };
+
+        if document.as_document().take_loaded_stylesheets() == 0 {
+            return false;
         }
+
+        self.record_css_profile(&document);
+        self.main_frame.restyle(&mut self.pipeline).await;
+        true
     }
 
     pub fn bitmap(&self) -> Option<&Bitmap> {
         self.main_frame.bitmap()
     }
 
+    pub fn scroll_height(&self) -> f32 {
+        self.pipeline
+            .content()
+            .map(|root| root.scroll_height())
+            .unwrap_or_default()
+    }
+
+    pub fn svg(&self) -> Option<String> {
+        self.pipeline.render_svg(&self.main_frame.size())
+    }
+
+    pub fn style_tree(&self) -> Vec<StyleTreeNode> {
+        self.main_frame
+            .document()
+            .map(StyleTreeNode::build_children)
+            .unwrap_or_default()
+    }
+
+    /// Wall time and counts of every stage that ran for the last loaded document.
+    pub fn profile(&self) -> Profile {
+        let mut profile = self.profile.clone();
+        profile.merge(self.pipeline.profile());
+        profile
+    }
+
+    pub fn dump_layout(&self) -> Option<String> {
+        self.pipeline.dump_layout()
+    }
+
+    pub fn dump_display_list(&self) -> Option<String> {
+        self.pipeline.dump_display_list(&self.main_frame.size())
+    }
+
     pub fn title(&self) -> String {
         self.main_frame
             .document()
@@ -130,36 +308,116 @@ impl Page {
         self.url.clone()
     }
 
-    fn fetch_html(&self, document_loader: DocumentLoader, url: Url) -> Result<String, LoadError> {
+    /// A handle aborting the next navigation, so that it can be cancelled from
+    /// another task while it's loading, e.g. when a newer navigation is requested.
+    pub fn next_navigation_abort_handle(&self) -> AbortHandle {
+        self.next_navigation.clone()
+    }
+
+    /// Cancel the requests of the previous navigation that are still queued or
+    /// in flight, as their response would be of no use to the next document.
+    fn start_navigation(&mut self, resource_loop_tx: Sender<LoadRequest>) -> DocumentLoader {
+        if let Some(loader) = self.loader.take() {
+            loader.abort();
+        }
+
+        let loader = DocumentLoader::new(resource_loop_tx)
+            .with_cache_mode(self.cache_mode)
+            .with_abort_handle(std::mem::take(&mut self.next_navigation));
+        self.loader = Some(loader.clone());
+        loader
+    }
+
+    async fn fetch_html(
+        &self,
+        document_loader: &DocumentLoader,
+        url: Url,
+    ) -> Result<Response, LoadError> {
         struct HTMLLoaderContext {
-            url: Url,
-            html_tx: Sender<Result<String, LoadError>>,
+            response_tx: Sender<Result<Response, LoadError>>,
         }
 
+        // The receiver is gone if the navigation was aborted in the meantime.
         impl FetchListener for HTMLLoaderContext {
-            fn on_finished(&self, bytes: loader::resource_loop::request::Bytes) {
-                if self.url.scheme == "view-source" {
-                    let raw_html = ByteString::new(&bytes).to_string();
-                    let raw_html_encoded = html_escape::encode_text(&raw_html);
-                    self.html_tx
-                        .send(Ok(format!("<pre>{}</pre>", raw_html_encoded)))
-                        .unwrap();
-                    return;
-                }
-                self.html_tx
-                    .send(Ok(ByteString::new(&bytes).to_string()))
-                    .unwrap();
+            fn on_finished(&self, response: Response) {
+                let _ = self.response_tx.send(Ok(response));
             }
 
             fn on_errored(&self, error: LoadError) {
-                self.html_tx.send(Err(error)).unwrap();
+                let _ = self.response_tx.send(Err(error));
             }
         }
 
         let (tx, rx) = bounded(1);
-        document_loader.fetch(url.clone(), HTMLLoaderContext { html_tx: tx, url });
+        let request = LoadRequest::new(url, Arc::new(HTMLLoaderContext { response_tx: tx }))
+            .with_header("Accept", "text/html,application/xhtml+xml,*/*;q=0.8")
+            .with_destination(Destination::Document);
+        document_loader.load(request);
+
+        // Queued requests only notice that they were aborted once the resource
+        // loop schedules again, so don't wait for it.
+        let abort_handle = document_loader.abort_handle();
+        tokio::select! {
+            response = rx.recv_async() => response.expect("Failed to receive HTML"),
+            _ = abort_handle.aborted() => Err(LoadError::Aborted),
+        }
+    }
+
+    /// Embedded stylesheets are parsed while the tree is built and tokens are
+    /// produced on demand, so both are subtracted from the tree building time.
+    fn record_parsing_profile<T: html::tokenizer::Tokenizing>(
+        &mut self,
+        document: &NodePtr,
+        tokenizer: &ProfiledTokenizer<T>,
+        html_start: Instant,
+        css_time_before_parsing: Duration,
+    ) {
+        let html_time = html_start.elapsed();
+        let embedded_css_time =
+            document.as_document().stylesheet_parse_time() - css_time_before_parsing;
+
+        self.profile = Profile::default();
+        self.profile.record(
+            Stage::HtmlTokenization,
+            tokenizer.time,
+            tokenizer.tokens_count,
+            "tokens",
+        );
+        self.profile.record(
+            Stage::HtmlTreeBuilding,
+            html_time.saturating_sub(tokenizer.time + embedded_css_time),
+            count_nodes(document),
+            "nodes",
+        );
+    }
 
-        rx.recv().expect("Failed to receive HTML")
+    fn record_css_profile(&mut self, document: &NodePtr) {
+        let document = document.as_document();
+        self.profile.record(
+            Stage::CssParsing,
+            document.stylesheet_parse_time(),
+            document.style_rules().len(),
+            "rules",
+        );
+    }
+
+    /// Wait for the external stylesheets so that the first frame is painted with
+    /// them. Stylesheets that take longer than the timeout are applied once they
+    /// arrive, see `apply_loaded_stylesheets`.
+    async fn wait_for_stylesheets(&self, document: &NodePtr) {
+        let document = document.as_document();
+        let deadline = tokio::time::Instant::now() + self.stylesheet_timeout;
+
+        while document.pending_stylesheets() > 0 {
+            let wait = tokio::time::timeout_at(deadline, document.wait_for_stylesheet());
+            if wait.await.is_err() {
+                log::warn!(
+                    "Rendering without {} stylesheets that are still loading",
+                    document.pending_stylesheets()
+                );
+                return;
+            }
+        }
     }
 
     fn get_error_page_content(&self, title: &str, error: &str) -> String {
@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use dom::node::NodePtr;
 use gfx::{Bitmap, Canvas, CanvasCPU, TextMeasure};
 use layout::{
@@ -5,16 +7,22 @@ use layout::{
     layout_box::{LayoutBox, LayoutBoxPtr},
     layout_context::LayoutContext,
 };
-use painting::Painter;
+use painting::{
+    display_list::{DisplayList, DisplayListBuilder},
+    svg, Painter,
+};
 use shared::{
     primitive::{Rect, Size},
     tree_node::TreeNode,
 };
 use style_types::ContextualRule;
 
+use crate::profile::{count_nodes, Profile, Stage};
+
 pub struct Pipeline {
     painter: Painter,
     layout_tree: Option<LayoutBoxPtr>,
+    profile: Profile,
 }
 
 pub struct PipelineRunOptions {
@@ -24,14 +32,21 @@ pub struct PipelineRunOptions {
 
 impl Pipeline {
     pub async fn new() -> Pipeline {
-        let painter = if std::env::var("CPU_RENDERING").is_ok() {
-            Painter::new(CanvasCPU::new())
-        } else {
-            Painter::new(Canvas::new().await)
-        };
+        if std::env::var("CPU_RENDERING").is_ok() {
+            return Pipeline::new_cpu();
+        }
+        Pipeline::with_painter(Painter::new(Canvas::new().await))
+    }
+
+    pub fn new_cpu() -> Pipeline {
+        Pipeline::with_painter(Painter::new(CanvasCPU::new()))
+    }
+
+    fn with_painter(painter: Painter) -> Pipeline {
         Pipeline {
             painter,
             layout_tree: None,
+            profile: Profile::default(),
         }
     }
 
@@ -41,37 +56,89 @@ impl Pipeline {
         size: &Size,
         opts: PipelineRunOptions,
     ) -> Bitmap {
+        let run_start = Instant::now();
+
         if !opts.skip_style_calculation {
-            self.calculate_styles(document_node.clone());
+            let start = Instant::now();
+            let nodes_count = self.calculate_styles(document_node.clone());
+            self.profile.record(
+                Stage::StyleCalculation,
+                start.elapsed(),
+                nodes_count,
+                "nodes",
+            );
         }
 
         if !opts.skip_layout_calculation {
+            let start = Instant::now();
             self.layout_tree = self.calculate_layout(document_node, size);
+            let boxes_count = self
+                .layout_tree
+                .as_ref()
+                .map(|node| count_nodes(node))
+                .unwrap_or_default();
+            self.profile
+                .record(Stage::Layout, start.elapsed(), boxes_count, "boxes");
         }
 
+        let start = Instant::now();
         self.painter.resize(size.clone());
-        if let Some(node) = &self.layout_tree {
-            self.painter.paint(node);
-        }
-        self.painter.output().await
+        let commands_count = match &self.layout_tree {
+            Some(node) => self.painter.paint(node),
+            None => 0,
+        };
+        let bitmap = self.painter.output().await;
+        self.profile
+            .record(Stage::Painting, start.elapsed(), commands_count, "commands");
+
+        self.profile.pipeline_run = run_start.elapsed();
+        bitmap
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
     }
 
     pub fn content(&self) -> Option<LayoutBoxPtr> {
         self.layout_tree.clone()
     }
 
-    fn calculate_styles(&self, document_node: NodePtr) {
+    pub fn dump_layout(&self) -> Option<String> {
+        self.layout_tree.as_ref().map(|node| node.dump(0))
+    }
+
+    pub fn display_list(&self, size: &Size) -> Option<DisplayList> {
+        self.layout_tree
+            .as_ref()
+            .map(|node| DisplayListBuilder::new(size).build(node))
+    }
+
+    pub fn dump_display_list(&self, size: &Size) -> Option<String> {
+        self.display_list(size)
+            .map(|display_list| display_list.dump())
+    }
+
+    pub fn render_svg(&self, size: &Size) -> Option<String> {
+        self.display_list(size)
+            .map(|display_list| svg::display_list_to_svg(&display_list, size))
+    }
+
+    /// Compute the styles of every node in the document and return the number of styled nodes.
+    fn calculate_styles(&self, document_node: NodePtr) -> usize {
         let document = document_node.as_document();
         let style_rules = document.style_rules();
 
-        fn compute_styles(element: NodePtr, style_rules: &[ContextualRule]) {
+        fn compute_styles(element: NodePtr, style_rules: &[ContextualRule]) -> usize {
             let computed_styles = style::compute::compute_styles(element.clone(), &style_rules);
             element.set_computed_styles(computed_styles);
 
-            element.for_each_child(|child| compute_styles(NodePtr(child), style_rules))
+            let mut nodes_count = 1;
+            element
+                .for_each_child(|child| nodes_count += compute_styles(NodePtr(child), style_rules));
+            nodes_count
         }
 
-        compute_styles(document_node, &style_rules);
+        compute_styles(document_node, &style_rules)
     }
 
     fn calculate_layout(&self, document_node: NodePtr, size: &Size) -> Option<LayoutBoxPtr> {
@@ -0,0 +1,150 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display},
+    time::Duration,
+};
+
+use html::tokenizer::{state::State, token::Token, Tokenizing};
+use serde::{Serialize, Serializer};
+use shared::tree_node::{TreeNode, TreeNodeHooks};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    HtmlTokenization,
+    HtmlTreeBuilding,
+    CssParsing,
+    StyleCalculation,
+    Layout,
+    Painting,
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::HtmlTokenization => "html_tokenization",
+            Stage::HtmlTreeBuilding => "html_tree_building",
+            Stage::CssParsing => "css_parsing",
+            Stage::StyleCalculation => "style_calculation",
+            Stage::Layout => "layout",
+            Stage::Painting => "painting",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageProfile {
+    pub stage: Stage,
+    #[serde(rename = "time_ms", serialize_with = "serialize_millis")]
+    pub time: Duration,
+    pub count: usize,
+    pub unit: &'static str,
+}
+
+/// Wall time and item counts of every stage that ran for the current document.
+/// Stages that are skipped on a re-render keep the numbers from their last run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Profile {
+    #[serde(rename = "load_html_ms", serialize_with = "serialize_millis")]
+    pub load_html: Duration,
+    #[serde(rename = "pipeline_run_ms", serialize_with = "serialize_millis")]
+    pub pipeline_run: Duration,
+    #[serde(serialize_with = "serialize_stages")]
+    pub stages: BTreeMap<Stage, StageProfile>,
+}
+
+impl Profile {
+    pub fn record(&mut self, stage: Stage, time: Duration, count: usize, unit: &'static str) {
+        self.stages.insert(
+            stage,
+            StageProfile {
+                stage,
+                time,
+                count,
+                unit,
+            },
+        );
+    }
+
+    pub fn merge(&mut self, other: &Profile) {
+        self.pipeline_run = other.pipeline_run;
+        for (stage, profile) in &other.stages {
+            self.stages.insert(*stage, profile.clone());
+        }
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<20} {:>12} {:>16}", "stage", "time (ms)", "count")?;
+        for profile in self.stages.values() {
+            writeln!(
+                f,
+                "{:<20} {:>12.3} {:>16}",
+                profile.stage.name(),
+                millis(&profile.time),
+                format!("{} {}", profile.count, profile.unit)
+            )?;
+        }
+        writeln!(f, "{:<20} {:>12.3}", "load_html", millis(&self.load_html))?;
+        writeln!(
+            f,
+            "{:<20} {:>12.3}",
+            "pipeline_run",
+            millis(&self.pipeline_run)
+        )
+    }
+}
+
+/// Wraps an HTML tokenizer to measure the time the tree builder spends waiting
+/// for tokens, so that tokenization can be reported apart from tree building.
+pub struct ProfiledTokenizer<T: Tokenizing> {
+    inner: T,
+    pub time: Duration,
+    pub tokens_count: usize,
+}
+
+impl<T: Tokenizing> ProfiledTokenizer<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            time: Duration::ZERO,
+            tokens_count: 0,
+        }
+    }
+}
+
+impl<T: Tokenizing> Tokenizing for &mut ProfiledTokenizer<T> {
+    fn next_token(&mut self) -> Token {
+        let start = std::time::Instant::now();
+        let token = self.inner.next_token();
+        self.time += start.elapsed();
+        self.tokens_count += 1;
+        token
+    }
+
+    fn switch_to(&mut self, state: State) {
+        self.inner.switch_to(state);
+    }
+}
+
+pub fn count_nodes<T: TreeNodeHooks<T> + Debug>(node: &TreeNode<T>) -> usize {
+    let mut count = 1;
+    node.for_each_child(|child| count += count_nodes(&child));
+    count
+}
+
+fn millis(duration: &Duration) -> f64 {
+    duration.as_secs_f64() * 1000.
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(millis(duration))
+}
+
+fn serialize_stages<S: Serializer>(
+    stages: &BTreeMap<Stage, StageProfile>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(stages.values())
+}
@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use dom::node::NodePtr;
+use serde::{Deserialize, Serialize};
+use style_types::{Property, Value};
+
+/// Snapshot of an element and its computed styles, used to export the style
+/// tree of a document.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StyleTreeNode {
+    pub tag: String,
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub computed_styles: BTreeMap<Property, Value>,
+    pub children: Vec<StyleTreeNode>,
+}
+
+impl StyleTreeNode {
+    pub fn build(node: NodePtr) -> Option<Self> {
+        let element = node.as_element_opt()?;
+
+        let class_list = element.class_list();
+        let class_list = class_list.borrow();
+        let classes = (0..class_list.length())
+            .filter_map(|index| class_list.item(index))
+            .collect();
+
+        let computed_styles = node
+            .computed_styles()
+            .iter()
+            .map(|(property, value)| (property.clone(), value.clone()))
+            .collect();
+
+        Some(Self {
+            tag: element.tag_name(),
+            id: element.id(),
+            classes,
+            computed_styles,
+            children: Self::build_children(node.clone()),
+        })
+    }
+
+    pub fn build_children(node: NodePtr) -> Vec<Self> {
+        let mut children = Vec::new();
+        node.for_each_child(|child| {
+            if let Some(child) = Self::build(NodePtr(child)) {
+                children.push(child);
+            }
+        });
+        children
+    }
+}
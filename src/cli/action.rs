@@ -1,38 +1,280 @@
+use std::path::{Path, PathBuf};
+
 use clap::ArgMatches;
+use loader::resource_loop::archive::NetworkArchive;
 
 pub enum Action {
     RenderOnce(RenderOnceParams),
+    RenderBatch(RenderBatchParams),
+    Dump(DumpParams),
+    TestFixtures(TestFixturesParams),
+    Serve(ServeParams),
     StartMain,
 }
 
+pub enum DocumentSource {
+    File(String),
+    Stdin { base_url: Option<String> },
+    Url(String),
+}
+
+#[derive(Clone, Copy)]
+pub enum OutputFormat {
+    Png,
+    Ppm,
+    Rgba,
+    Svg,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "png" => Some(OutputFormat::Png),
+            "ppm" => Some(OutputFormat::Ppm),
+            "rgba" => Some(OutputFormat::Rgba),
+            "svg" => Some(OutputFormat::Svg),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Ppm => "ppm",
+            OutputFormat::Rgba => "rgba",
+            OutputFormat::Svg => "svg",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ProfileFormat {
+    Table,
+    Json,
+}
+
 pub struct RenderOnceParams {
-    pub html_path: String,
+    pub source: DocumentSource,
     pub viewport_size: (u32, u32),
     pub output_path: String,
+    pub output_format: OutputFormat,
+    pub full_page: bool,
+    pub deterministic: bool,
+    pub profile_format: Option<ProfileFormat>,
+    pub archive: Option<NetworkArchive>,
+}
+
+pub struct RenderBatchParams {
+    pub sources: Vec<DocumentSource>,
+    pub viewport_sizes: Vec<(u32, u32)>,
+    pub output_dir: String,
+    pub output_format: OutputFormat,
+    pub full_page: bool,
+    pub deterministic: bool,
+    pub profile_format: Option<ProfileFormat>,
+    pub archive: Option<NetworkArchive>,
+}
+
+pub struct DumpParams {
+    pub html_path: String,
+    pub viewport_size: (u32, u32),
+    pub dump_layout: bool,
+    pub dump_display_list: bool,
+    pub dump_styles: bool,
+}
+
+pub struct TestFixturesParams {
+    pub fixtures_path: String,
+    pub references_path: String,
+    pub diff_output_path: String,
+    pub viewport_size: (u32, u32),
+    pub tolerance: u8,
+    pub update_references: bool,
+}
+
+pub struct ServeParams {
+    pub viewport_size: (u32, u32),
+    pub frames_dir: Option<PathBuf>,
+}
+
+pub fn get_user_agent(matches: &ArgMatches) -> Option<String> {
+    let matches = matches
+        .subcommand()
+        .map(|(_, matches)| matches)
+        .unwrap_or(matches);
+    matches.get_one::<String>("user-agent").cloned()
 }
 
 pub fn get_action<'a>(matches: ArgMatches) -> Action {
     if let Some(matches) = matches.subcommand_matches("render") {
-        let html = matches.get_one::<String>("html").expect("Required");
-        let raw_size = matches.get_one::<String>("size").expect("Required");
+        let html = matches.get_one::<String>("html");
+        let manifest = matches.get_one::<String>("manifest");
+        let url = matches.get_one::<String>("url");
+        let base_url = matches.get_one::<String>("base-url");
+        let raw_sizes = matches.get_one::<String>("size").expect("Required");
         let output_path = matches.get_one::<String>("output").expect("Required");
 
         let is_render_once = matches.get_flag("once");
+        let full_page = matches.get_flag("full-page");
+        let deterministic = matches.get_flag("deterministic");
+        let profile_format =
+            matches
+                .get_one::<String>("profile")
+                .map(|format| match format.as_str() {
+                    "json" => ProfileFormat::Json,
+                    _ => ProfileFormat::Table,
+                });
+
+        // An explicit format wins over the extension of the output file.
+        let output_format = matches
+            .get_one::<String>("format")
+            .and_then(|format| OutputFormat::from_name(format))
+            .or_else(|| {
+                Path::new(output_path)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .and_then(OutputFormat::from_name)
+            })
+            .unwrap_or(OutputFormat::Png);
 
-        let viewport_size = parse_size(&raw_size);
+        let viewport_sizes = raw_sizes.split(',').map(parse_size).collect::<Vec<_>>();
+        let archive = get_archive(matches);
 
         if is_render_once {
-            return Action::RenderOnce(RenderOnceParams {
-                html_path: html.clone(),
-                output_path: output_path.clone(),
-                viewport_size,
+            let is_html_dir = html.is_some_and(|html| Path::new(html).is_dir());
+            let is_batch = manifest.is_some() || is_html_dir || viewport_sizes.len() > 1;
+
+            let mut sources = match (html, manifest, url) {
+                (_, _, Some(url)) => vec![DocumentSource::Url(url.clone())],
+                (_, Some(manifest), _) => read_manifest(manifest)
+                    .into_iter()
+                    .map(DocumentSource::File)
+                    .collect(),
+                (Some(html), _, _) if html == "-" => vec![DocumentSource::Stdin {
+                    base_url: base_url.cloned(),
+                }],
+                (Some(html), _, _) if is_html_dir => read_html_dir(html)
+                    .into_iter()
+                    .map(DocumentSource::File)
+                    .collect(),
+                (Some(html), _, _) => vec![DocumentSource::File(html.clone())],
+                (None, None, None) => unreachable!(),
+            };
+
+            if !is_batch {
+                return Action::RenderOnce(RenderOnceParams {
+                    source: sources.remove(0),
+                    output_path: output_path.clone(),
+                    viewport_size: viewport_sizes[0],
+                    output_format,
+                    full_page,
+                    deterministic,
+                    profile_format,
+                    archive,
+                });
+            }
+
+            return Action::RenderBatch(RenderBatchParams {
+                sources,
+                viewport_sizes,
+                output_dir: output_path.clone(),
+                output_format,
+                full_page,
+                deterministic,
+                profile_format,
+                archive,
             });
         }
     }
 
+    if let Some(matches) = matches.subcommand_matches("dump") {
+        let html = matches.get_one::<String>("html").expect("Required");
+        let raw_size = matches.get_one::<String>("size").expect("Default");
+
+        let dump_layout = matches.get_flag("layout");
+        let dump_display_list = matches.get_flag("display-list");
+        let dump_styles = matches.get_flag("styles");
+
+        let viewport_size = parse_size(raw_size);
+
+        return Action::Dump(DumpParams {
+            html_path: html.clone(),
+            viewport_size,
+            dump_layout,
+            dump_display_list,
+            dump_styles,
+        });
+    }
+
+    if let Some(matches) = matches.subcommand_matches("test-fixtures") {
+        let fixtures = matches.get_one::<String>("fixtures").expect("Default");
+        let references = matches.get_one::<String>("references").expect("Default");
+        let diff_output = matches.get_one::<String>("diff-output").expect("Default");
+        let raw_size = matches.get_one::<String>("size").expect("Default");
+        let tolerance = *matches.get_one::<u8>("tolerance").expect("Default");
+
+        let update_references = matches.get_flag("update");
+
+        let viewport_size = parse_size(raw_size);
+
+        return Action::TestFixtures(TestFixturesParams {
+            fixtures_path: fixtures.clone(),
+            references_path: references.clone(),
+            diff_output_path: diff_output.clone(),
+            viewport_size,
+            tolerance,
+            update_references,
+        });
+    }
+
+    if let Some(matches) = matches.subcommand_matches("serve") {
+        let raw_size = matches.get_one::<String>("size").expect("Default");
+        let frames_dir = matches.get_one::<String>("frames-dir").map(PathBuf::from);
+
+        return Action::Serve(ServeParams {
+            viewport_size: parse_size(raw_size),
+            frames_dir,
+        });
+    }
+
     Action::StartMain
 }
 
+fn get_archive(matches: &ArgMatches) -> Option<NetworkArchive> {
+    if let Some(path) = matches.get_one::<String>("record") {
+        return Some(NetworkArchive::record(path).expect("Unable to create archive"));
+    }
+
+    matches
+        .get_one::<String>("replay")
+        .map(|path| NetworkArchive::replay(path).expect("Unable to read archive"))
+}
+
+fn read_html_dir(dir: &str) -> Vec<String> {
+    let mut html_paths = std::fs::read_dir(dir)
+        .expect("Unable to read HTML directory")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .map(|path| path.to_str().unwrap().to_string())
+        .collect::<Vec<String>>();
+    html_paths.sort();
+    html_paths
+}
+
+/// A manifest lists one HTML file per line, relative to the manifest itself.
+/// Empty lines and lines starting with `#` are ignored.
+fn read_manifest(manifest_path: &str) -> Vec<String> {
+    let manifest = std::fs::read_to_string(manifest_path).expect("Unable to read manifest");
+    let manifest_dir = Path::new(manifest_path).parent().unwrap_or(Path::new(""));
+
+    manifest
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| manifest_dir.join(line).to_str().unwrap().to_string())
+        .collect()
+}
+
 fn parse_size(raw_size: &str) -> (u32, u32) {
     let size_params = raw_size
         .split('x')
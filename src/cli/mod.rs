@@ -1,27 +1,185 @@
 mod action;
 
 pub use action::*;
-use clap::{Arg, ArgMatches, Command};
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
 
 const AUTHOR: &'static str = "Viet-Hung Nguyen <viethungax@gmail.com>";
 
 pub fn accept_cli() -> ArgMatches {
     let html_file_arg = Arg::new("html").long("html").required(false);
     let size_arg = Arg::new("size").long("size").required(true);
-    let once_flag = Arg::new("once").long("once");
+    let once_flag = Arg::new("once").long("once").action(ArgAction::SetTrue);
     let ouput_arg = Arg::new("output").long("output").required(true);
 
     let render_once_subcommand = Command::new("render")
         .about("Start a rendering process of Moon and render once")
         .author(AUTHOR)
-        .arg(html_file_arg.clone().required(true))
-        .arg(size_arg.clone())
+        .arg(
+            html_file_arg
+                .clone()
+                .help("HTML file, a directory of HTML files, or - to read from stdin"),
+        )
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .help("URL of the document to render"),
+        )
+        .arg(
+            Arg::new("base-url")
+                .long("base-url")
+                .requires("html")
+                .help("Base URL of the HTML read from stdin (defaults to the working directory)"),
+        )
+        .arg(
+            Arg::new("manifest")
+                .long("manifest")
+                .help("File listing the HTML files to render, one per line"),
+        )
+        .group(
+            ArgGroup::new("input")
+                .args(["html", "manifest", "url"])
+                .required(true),
+        )
+        .arg(
+            size_arg
+                .clone()
+                .help("Viewport size, or a comma-separated list of sizes (e.g. 1200x600,400x800)"),
+        )
         .arg(once_flag.clone())
-        .arg(ouput_arg.clone());
+        .arg(
+            Arg::new("full-page")
+                .long("full-page")
+                .action(ArgAction::SetTrue)
+                .help("Extend the viewport height to capture the whole page"),
+        )
+        .arg(
+            Arg::new("deterministic")
+                .long("deterministic")
+                .action(ArgAction::SetTrue)
+                .help("Render on the CPU and load resources sequentially for reproducible output"),
+        )
+        .arg(ouput_arg.clone().help(
+            "Output file (- for stdout), or a directory when rendering several documents or sizes",
+        ))
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["png", "ppm", "rgba", "svg"])
+                .help("Output format, guessed from the output file extension by default"),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .conflicts_with("replay")
+                .help("Record every remote response to the given archive file"),
+        )
+        .arg(
+            Arg::new("replay")
+                .long("replay")
+                .help("Serve remote resources from an archive file instead of the network"),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .num_args(0..=1)
+                .default_missing_value("table")
+                .value_parser(["table", "json"])
+                .help("Print the time and item count of every pipeline stage to stderr"),
+        );
+
+    let layout_flag = Arg::new("layout")
+        .long("layout")
+        .action(ArgAction::SetTrue)
+        .help("Print the layout tree");
+    let display_list_flag = Arg::new("display-list")
+        .long("display-list")
+        .action(ArgAction::SetTrue)
+        .help("Print the display list");
+    let styles_flag = Arg::new("styles")
+        .long("styles")
+        .action(ArgAction::SetTrue)
+        .help("Print the computed styles of every element as JSON");
+
+    let dump_subcommand = Command::new("dump")
+        .about("Run the rendering pipeline of Moon and print its intermediate output")
+        .author(AUTHOR)
+        .arg(html_file_arg.clone().required(true))
+        .arg(size_arg.clone().required(false).default_value("1200x600"))
+        .arg(layout_flag)
+        .arg(display_list_flag)
+        .arg(styles_flag)
+        .group(
+            ArgGroup::new("target")
+                .args(["layout", "display-list", "styles"])
+                .required(true)
+                .multiple(true),
+        );
+
+    let test_fixtures_subcommand = Command::new("test-fixtures")
+        .about("Render every fixture on the CPU and compare it against its reference image")
+        .author(AUTHOR)
+        .arg(
+            Arg::new("fixtures")
+                .long("fixtures")
+                .default_value("fixtures")
+                .help("Directory containing the HTML fixtures"),
+        )
+        .arg(
+            Arg::new("references")
+                .long("references")
+                .default_value("fixtures/references")
+                .help("Directory containing the reference PNGs"),
+        )
+        .arg(
+            Arg::new("diff-output")
+                .long("diff-output")
+                .default_value("output/diff")
+                .help("Directory to write the actual and diff images of mismatched fixtures"),
+        )
+        .arg(size_arg.clone().required(false).default_value("1200x600"))
+        .arg(
+            Arg::new("tolerance")
+                .long("tolerance")
+                .default_value("0")
+                .value_parser(clap::value_parser!(u8))
+                .help("Maximum difference allowed per color channel of a pixel"),
+        )
+        .arg(
+            Arg::new("update")
+                .long("update")
+                .action(ArgAction::SetTrue)
+                .help("Overwrite the reference PNGs with the current output"),
+        );
+
+    let serve_subcommand = Command::new("serve")
+        .about("Drive the render engine of Moon with JSON commands over stdin/stdout")
+        .author(AUTHOR)
+        .arg(
+            Arg::new("stdio")
+                .long("stdio")
+                .action(ArgAction::SetTrue)
+                .required(true)
+                .help(
+                    "Read commands from stdin and write events to stdout, one JSON object per line",
+                ),
+        )
+        .arg(size_arg.clone().required(false).default_value("1200x600"))
+        .arg(Arg::new("frames-dir").long("frames-dir").help(
+            "Write frames as PNG files to this directory instead of inlining them as base64",
+        ));
 
     Command::new("Moon Renderer")
         .author(AUTHOR)
         .about("Moon web browser!")
+        .arg(
+            Arg::new("user-agent")
+                .long("user-agent")
+                .global(true)
+                .help("User-Agent header sent with HTTP requests"),
+        )
         .subcommand(render_once_subcommand)
+        .subcommand(dump_subcommand)
+        .subcommand(test_fixtures_subcommand)
+        .subcommand(serve_subcommand)
         .get_matches()
 }
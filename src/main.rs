@@ -1,24 +1,224 @@
 mod cli;
+mod output;
+mod serve;
+mod test_fixtures;
 
-use image::{ImageBuffer, Rgba};
-use loader::resource_loop::ResourceLoop;
-use render::page::Page;
+use cli::{DocumentSource, ProfileFormat};
+use flume::Sender;
+use loader::resource_loop::{archive::NetworkArchive, request::LoadRequest, ResourceLoop};
+use render::{
+    page::{Page, DETERMINISTIC_STYLESHEET_LOAD_TIMEOUT},
+    profile::Profile,
+};
 use shared::primitive::Size;
 use simplelog::*;
-use std::io::Read;
-use url::parser::URLParser;
+use std::{collections::HashSet, io::Read, path::Path};
+use url::{parser::URLParser, Url};
 
-fn read_file(path: String) -> String {
-    let mut file = std::fs::File::open(path).expect("Unable to open file");
-    let mut result = String::new();
+fn read_file(path: String) -> Vec<u8> {
+    std::fs::read(path).expect("Unable to read file!")
+}
+
+fn read_stdin() -> Vec<u8> {
+    let mut result = Vec::new();
+
+    std::io::stdin()
+        .read_to_end(&mut result)
+        .expect("Unable to read stdin!");
+
+    result
+}
+
+fn get_base_url(html_path: &str) -> Url {
+    let absolute_html_path = std::fs::canonicalize(html_path).unwrap();
+    let absolute_path = absolute_html_path.parent().unwrap();
+    let absolute_path_url = format!("file://{}/", absolute_path.to_str().unwrap());
+    URLParser::parse(&absolute_path_url, None).unwrap()
+}
+
+/// Documents read from stdin resolve their relative resources against the working directory.
+fn get_working_dir_url() -> Url {
+    let working_dir = std::env::current_dir().unwrap();
+    let working_dir_url = format!("file://{}/", working_dir.to_str().unwrap());
+    URLParser::parse(&working_dir_url, None).unwrap()
+}
+
+fn get_source_name(source: &DocumentSource) -> String {
+    let name = match source {
+        DocumentSource::File(path) => Path::new(path).file_stem().and_then(|stem| stem.to_str()),
+        DocumentSource::Url(url) => {
+            let url = url.split(['?', '#']).next().unwrap_or_default();
+            let last_segment = url.trim_end_matches('/').rsplit('/').next();
+            last_segment
+                .and_then(|segment| Path::new(segment).file_stem())
+                .and_then(|stem| stem.to_str())
+        }
+        DocumentSource::Stdin { .. } => Some("stdin"),
+    };
+    name.filter(|name| !name.is_empty())
+        .unwrap_or("index")
+        .to_string()
+}
+
+async fn load_source(
+    page: &mut Page,
+    source: &DocumentSource,
+    resource_loop_tx: Sender<LoadRequest>,
+) {
+    match source {
+        DocumentSource::File(path) => {
+            page.load_html_bytes(
+                &read_file(path.clone()),
+                get_base_url(path),
+                resource_loop_tx,
+            )
+            .await
+        }
+        DocumentSource::Stdin { base_url } => {
+            let base_url = match base_url {
+                Some(base_url) => URLParser::parse(base_url, None).expect("Invalid base URL"),
+                None => get_working_dir_url(),
+            };
+            page.load_html_bytes(&read_stdin(), base_url, resource_loop_tx)
+                .await
+        }
+        DocumentSource::Url(url) => page.load_raw_url(url.clone(), resource_loop_tx).await,
+    }
+}
+
+/// Deterministic pages always paint on the CPU, fetch their resources
+/// sequentially and wait for every stylesheet so that the output is identical
+/// across runs. Remote resources are recorded to or replayed from `archive`.
+async fn new_page(
+    viewport: (u32, u32),
+    deterministic: bool,
+    archive: Option<NetworkArchive>,
+) -> (Page, Sender<LoadRequest>) {
+    let (width, height) = viewport;
+    let size = Size::new(width as f32, height as f32);
+
+    if deterministic {
+        let resource_loop = ResourceLoop::new_deterministic().with_archive(archive);
+        let mut page = Page::new_cpu(size);
+        page.set_stylesheet_timeout(DETERMINISTIC_STYLESHEET_LOAD_TIMEOUT);
+        return (page, resource_loop.start_loop());
+    }
+
+    let resource_loop = ResourceLoop::new().with_archive(archive);
+    (Page::new(size).await, resource_loop.start_loop())
+}
+
+async fn load_page(
+    source: &DocumentSource,
+    viewport: (u32, u32),
+    deterministic: bool,
+    archive: Option<NetworkArchive>,
+) -> Page {
+    let (mut page, resource_loop_tx) = new_page(viewport, deterministic, archive).await;
+    load_source(&mut page, source, resource_loop_tx).await;
+    page
+}
 
-    file.read_to_string(&mut result)
-        .expect("Unable to read file!");
+/// Grow the page vertically until the whole document fits without scrolling.
+async fn expand_to_full_page(page: &mut Page) {
+    let size = page.size();
+    let full_height = page.scroll_height().ceil();
 
-    return result;
+    if full_height > size.height {
+        page.resize(Size::new(size.width, full_height)).await;
+    }
+}
+
+fn print_profile(name: &str, profile: &Profile, format: ProfileFormat) {
+    match format {
+        ProfileFormat::Table => eprintln!("{}\n{}", name, profile),
+        ProfileFormat::Json => {
+            let mut json = serde_json::to_value(profile).unwrap();
+            json["source"] = serde_json::Value::String(name.to_string());
+            eprintln!("{}", json);
+        }
+    }
+}
+
+async fn render_batch(params: cli::RenderBatchParams) {
+    let extension = params.output_format.extension();
+
+    // Outputs are named after their source file only, so `a/index.html` and
+    // `b/index.html` would overwrite each other.
+    let names = params
+        .sources
+        .iter()
+        .map(get_source_name)
+        .collect::<Vec<_>>();
+    let mut seen_names = HashSet::new();
+    if let Some(duplicate) = names.iter().find(|name| !seen_names.insert(*name)) {
+        log::error!(
+            "Several sources would be rendered to {}.{}, render them separately",
+            duplicate,
+            extension
+        );
+        std::process::exit(1);
+    }
+
+    let output_dir = Path::new(&params.output_dir);
+    std::fs::create_dir_all(output_dir).expect("Unable to create output directory");
+
+    let (mut page, resource_loop_tx) = new_page(
+        params.viewport_sizes[0],
+        params.deterministic,
+        params.archive,
+    )
+    .await;
+
+    for (source, name) in params.sources.iter().zip(names) {
+        log::info!("Rendering {}", name);
+        load_source(&mut page, source, resource_loop_tx.clone()).await;
+
+        for &(width, height) in &params.viewport_sizes {
+            let size = Size::new(width as f32, height as f32);
+            if page.size() != size {
+                page.resize(size).await;
+            }
+
+            if params.full_page {
+                expand_to_full_page(&mut page).await;
+            }
+
+            if let Some(format) = params.profile_format {
+                print_profile(
+                    &format!("{} ({}x{})", name, width, height),
+                    &page.profile(),
+                    format,
+                );
+            }
+
+            let file_name = if params.viewport_sizes.len() == 1 {
+                format!("{}.{}", name, extension)
+            } else {
+                format!("{}_{}x{}.{}", name, width, height, extension)
+            };
+            output::write(
+                output_dir.join(file_name),
+                &output::encode(&page, params.output_format),
+            );
+        }
+    }
 }
 
 fn main() {
+    let matches = cli::accept_cli();
+    if let Some(user_agent) = cli::get_user_agent(&matches) {
+        net::http::set_user_agent(&user_agent);
+    }
+    let action = cli::get_action(matches);
+
+    // Keep the logs out of the way when the result is printed to stdout.
+    let log_level = match &action {
+        cli::Action::Dump(_) | cli::Action::Serve(_) => LevelFilter::Warn,
+        cli::Action::RenderOnce(params) if params.output_path == "-" => LevelFilter::Warn,
+        _ => LevelFilter::Debug,
+    };
+
     let config = ConfigBuilder::new()
         .add_filter_ignore_str("wgpu")
         .add_filter_ignore_str("iced_wgpu")
@@ -27,42 +227,70 @@ fn main() {
         .add_filter_ignore_str("naga")
         .set_target_level(LevelFilter::Info)
         .build();
-    TermLogger::init(
-        LevelFilter::Debug,
-        config,
-        TerminalMode::Mixed,
-        ColorChoice::Auto,
-    )
-    .unwrap();
-
-    let action = cli::get_action(cli::accept_cli());
+    TermLogger::init(log_level, config, TerminalMode::Mixed, ColorChoice::Auto).unwrap();
 
     match action {
         cli::Action::RenderOnce(params) => {
-            let html_code = read_file(params.html_path.clone());
             let viewport = params.viewport_size;
             let output_path = params.output_path;
+            let source = params.source;
+            let full_page = params.full_page;
+            let output_format = params.output_format;
+            let deterministic = params.deterministic;
+            let profile_format = params.profile_format;
+            let archive = params.archive;
 
-            let absolute_html_path = std::fs::canonicalize(params.html_path).unwrap();
-            let absolute_path = absolute_html_path.parent().unwrap();
-            let absolute_path_url = format!("file://{}/", absolute_path.to_str().unwrap());
-            let base_url = URLParser::parse(&absolute_path_url, None).unwrap();
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async {
+                let mut page = load_page(&source, viewport, deterministic, archive).await;
 
-            let (width, height) = viewport;
+                if full_page {
+                    expand_to_full_page(&mut page).await;
+                }
+
+                if let Some(format) = profile_format {
+                    print_profile(&get_source_name(&source), &page.profile(), format);
+                }
+
+                output::write(output_path, &output::encode(&page, output_format));
+            });
+        }
+        cli::Action::RenderBatch(params) => {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(render_batch(params));
+        }
+        cli::Action::Dump(params) => {
+            let source = DocumentSource::File(params.html_path.clone());
+            let viewport = params.viewport_size;
 
             let rt = tokio::runtime::Runtime::new().unwrap();
             rt.block_on(async {
-                let resource_loop = ResourceLoop::new();
-                let resource_loop_tx = resource_loop.start_loop();
-                let mut page = Page::new(Size::new(width as f32, height as f32)).await;
-                page.load_html(html_code.to_string(), base_url, resource_loop_tx)
-                    .await;
-                let bitmap = page.bitmap().unwrap().clone();
-
-                let buffer = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, bitmap).unwrap();
-                buffer.save(output_path).unwrap();
+                // Dumps don't need a GPU and are meant to be diffed.
+                let page = load_page(&source, viewport, true, None).await;
+
+                if params.dump_layout {
+                    println!("{}", page.dump_layout().unwrap_or_default());
+                }
+
+                if params.dump_display_list {
+                    println!("{}", page.dump_display_list().unwrap_or_default());
+                }
+
+                if params.dump_styles {
+                    let style_tree = page.style_tree();
+                    println!("{}", serde_json::to_string_pretty(&style_tree).unwrap());
+                }
             });
         }
+        cli::Action::TestFixtures(params) => {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            let passed = rt.block_on(test_fixtures::run(params));
+
+            if !passed {
+                std::process::exit(1);
+            }
+        }
+        cli::Action::Serve(params) => serve::run(params),
         cli::Action::StartMain => {
             main::start_main().expect("Browser crashed with error");
         }
@@ -0,0 +1,55 @@
+use std::{io::Write, path::Path};
+
+use image::{ImageBuffer, ImageFormat, Rgba};
+use render::page::Page;
+use shared::primitive::Size;
+
+use crate::cli::OutputFormat;
+
+/// Encode the current content of the page in the given format.
+pub fn encode(page: &Page, format: OutputFormat) -> Vec<u8> {
+    if let OutputFormat::Svg = format {
+        return page.svg().unwrap_or_default().into_bytes();
+    }
+
+    let bitmap = page.bitmap().unwrap().clone();
+    encode_bitmap(bitmap, &page.size(), format)
+}
+
+/// Encode a rendered bitmap. SVG is not supported because it is produced from
+/// the display list instead of the bitmap.
+pub fn encode_bitmap(bitmap: Vec<u8>, size: &Size, format: OutputFormat) -> Vec<u8> {
+    let (width, height) = (size.width as u32, size.height as u32);
+
+    match format {
+        OutputFormat::Png => {
+            let buffer = ImageBuffer::<Rgba<u8>, _>::from_raw(width, height, bitmap).unwrap();
+            let mut result = std::io::Cursor::new(Vec::new());
+            buffer.write_to(&mut result, ImageFormat::Png).unwrap();
+            result.into_inner()
+        }
+        OutputFormat::Ppm => {
+            let mut result = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+            for pixel in bitmap.chunks_exact(4) {
+                result.extend_from_slice(&pixel[..3]);
+            }
+            result
+        }
+        OutputFormat::Rgba => bitmap,
+        OutputFormat::Svg => unreachable!("SVG can only be encoded from a page"),
+    }
+}
+
+/// Write the encoded output to a file, or to stdout if the path is `-`.
+pub fn write(output_path: impl AsRef<Path>, bytes: &[u8]) {
+    let output_path = output_path.as_ref();
+
+    if output_path == Path::new("-") {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(bytes).expect("Unable to write to stdout");
+        stdout.flush().expect("Unable to write to stdout");
+        return;
+    }
+
+    std::fs::write(output_path, bytes).expect("Unable to write output file");
+}
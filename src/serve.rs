@@ -0,0 +1,191 @@
+use std::{
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use net::cookie::CookieJar;
+use render::{InputEvent, OutputEvent, RenderEngine};
+use serde::{Deserialize, Serialize};
+use shared::primitive::{Point, Size};
+use url::parser::URLParser;
+
+use crate::{
+    cli::{OutputFormat, ServeParams},
+    get_working_dir_url, output,
+};
+
+/// Commands accepted on stdin, one JSON object per line.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Command {
+    LoadHtml {
+        html: String,
+        base_url: Option<String>,
+    },
+    LoadUrl {
+        url: String,
+    },
+    Resize {
+        width: u32,
+        height: u32,
+    },
+    Scroll {
+        delta_y: f32,
+    },
+    MouseMove {
+        x: f32,
+        y: f32,
+    },
+    Reload,
+}
+
+/// Messages written to stdout, one JSON object per line.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    Frame {
+        width: u32,
+        height: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        data: Option<String>,
+    },
+    TitleChanged {
+        title: String,
+    },
+    UrlChanged {
+        url: String,
+    },
+    LoadingStarted,
+    LoadingFinished,
+    Error {
+        message: String,
+    },
+}
+
+/// Drive the render engine through a line-delimited JSON protocol over
+/// stdin/stdout. Returns once stdin is closed and every pending event has been
+/// written out.
+pub fn run(params: ServeParams) {
+    let (input_tx, input_rx) = flume::unbounded();
+    let (output_tx, output_rx) = flume::unbounded();
+
+    let (width, height) = params.viewport_size;
+    let engine_thread = std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let viewport = Size::new(width as f32, height as f32);
+            let render_engine = RenderEngine::new(viewport, CookieJar::new(), None).await;
+
+            // The engine only stops once the input channel is closed.
+            let _ = render_engine.run(input_rx, output_tx).await;
+        });
+    });
+
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match parse_command(&line) {
+                Ok(event) => {
+                    if input_tx.send(event).is_err() {
+                        break;
+                    }
+                }
+                Err(message) => emit(&Message::Error { message }),
+            }
+        }
+    });
+
+    if let Some(frames_dir) = &params.frames_dir {
+        std::fs::create_dir_all(frames_dir).expect("Unable to create frames directory");
+    }
+
+    let mut frame_count = 0;
+
+    for event in output_rx.iter() {
+        let message = match event {
+            OutputEvent::FrameRendered(bitmap, size) => {
+                frame_count += 1;
+                frame_message(bitmap, size, frame_count, params.frames_dir.as_deref())
+            }
+            OutputEvent::TitleChanged(title) => Message::TitleChanged { title },
+            OutputEvent::URLChanged(url) => Message::UrlChanged { url: url.as_str() },
+            OutputEvent::LoadingStarted => Message::LoadingStarted,
+            OutputEvent::LoadingFinished => Message::LoadingFinished,
+        };
+        emit(&message);
+    }
+
+    engine_thread.join().expect("Render engine crashed");
+}
+
+fn parse_command(line: &str) -> Result<InputEvent, String> {
+    let command =
+        serde_json::from_str::<Command>(line).map_err(|e| format!("Invalid command: {}", e))?;
+
+    let event = match command {
+        Command::LoadHtml { html, base_url } => {
+            let base_url = match base_url {
+                Some(base_url) => URLParser::parse(&base_url, None)
+                    .ok_or_else(|| format!("Invalid base URL: {}", base_url))?,
+                None => get_working_dir_url(),
+            };
+            InputEvent::LoadHTML { html, base_url }
+        }
+        Command::LoadUrl { url } => InputEvent::LoadRawURL(url),
+        Command::Resize { width, height } => {
+            InputEvent::ViewportResize(Size::new(width as f32, height as f32))
+        }
+        Command::Scroll { delta_y } => InputEvent::Scroll(delta_y),
+        Command::MouseMove { x, y } => InputEvent::MouseMove(Point::new(x, y)),
+        Command::Reload => InputEvent::Reload,
+    };
+
+    Ok(event)
+}
+
+fn frame_message(
+    bitmap: Vec<u8>,
+    size: Size,
+    frame_number: usize,
+    frames_dir: Option<&Path>,
+) -> Message {
+    let png = output::encode_bitmap(bitmap, &size, OutputFormat::Png);
+    let (width, height) = (size.width as u32, size.height as u32);
+
+    match frames_dir {
+        Some(frames_dir) => {
+            let path: PathBuf = frames_dir.join(format!("frame-{:05}.png", frame_number));
+            output::write(&path, &png);
+            Message::Frame {
+                width,
+                height,
+                path: Some(path.to_str().unwrap().to_string()),
+                data: None,
+            }
+        }
+        None => Message::Frame {
+            width,
+            height,
+            path: None,
+            data: Some(BASE64.encode(png)),
+        },
+    }
+}
+
+fn emit(message: &Message) {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, message).expect("Unable to write to stdout");
+    stdout.write_all(b"\n").expect("Unable to write to stdout");
+    stdout.flush().expect("Unable to write to stdout");
+}
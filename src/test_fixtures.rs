@@ -0,0 +1,178 @@
+use std::path::{Path, PathBuf};
+
+use image::{ImageBuffer, Rgba, RgbaImage};
+use loader::resource_loop::ResourceLoop;
+use render::page::{Page, DETERMINISTIC_STYLESHEET_LOAD_TIMEOUT};
+use shared::primitive::Size;
+
+use crate::{cli::TestFixturesParams, get_base_url, read_file};
+
+enum FixtureResult {
+    Passed,
+    Updated,
+    MissingReference,
+    SizeMismatch((u32, u32), (u32, u32)),
+    PixelMismatch(usize),
+}
+
+/// Render every fixture with the CPU canvas and compare the output with its reference image.
+/// Returns `true` if all fixtures match their references.
+pub async fn run(params: TestFixturesParams) -> bool {
+    let fixtures = collect_fixtures(&params.fixtures_path);
+    let references_path = Path::new(&params.references_path);
+    let diff_output_path = Path::new(&params.diff_output_path);
+    let (width, height) = params.viewport_size;
+
+    if params.update_references {
+        std::fs::create_dir_all(references_path).expect("Unable to create references directory");
+    }
+
+    let resource_loop = ResourceLoop::new_deterministic();
+    let resource_loop_tx = resource_loop.start_loop();
+    let mut page = Page::new_cpu(Size::new(width as f32, height as f32));
+    page.set_stylesheet_timeout(DETERMINISTIC_STYLESHEET_LOAD_TIMEOUT);
+
+    let mut failed_count = 0;
+
+    for fixture in &fixtures {
+        let name = fixture.file_stem().unwrap().to_str().unwrap().to_string();
+        let fixture_path = fixture.to_str().unwrap().to_string();
+
+        page.load_html_bytes(
+            &read_file(fixture_path.clone()),
+            get_base_url(&fixture_path),
+            resource_loop_tx.clone(),
+        )
+        .await;
+
+        let actual = ImageBuffer::<Rgba<u8>, _>::from_raw(
+            width,
+            height,
+            page.bitmap().cloned().unwrap_or_default(),
+        )
+        .expect("Invalid bitmap size");
+
+        let reference_path = references_path.join(format!("{}.png", name));
+
+        let result = if params.update_references {
+            actual.save(&reference_path).unwrap();
+            FixtureResult::Updated
+        } else {
+            compare_with_reference(&actual, &reference_path, params.tolerance)
+        };
+
+        match &result {
+            FixtureResult::Passed => log::info!("[PASSED] {}", name),
+            FixtureResult::Updated => log::info!("[UPDATED] {}", name),
+            FixtureResult::MissingReference => {
+                log::error!("[FAILED] {}: missing reference image", name)
+            }
+            FixtureResult::SizeMismatch(expected, actual) => log::error!(
+                "[FAILED] {}: expected size {}x{} but got {}x{}",
+                name,
+                expected.0,
+                expected.1,
+                actual.0,
+                actual.1
+            ),
+            FixtureResult::PixelMismatch(count) => {
+                log::error!("[FAILED] {}: {} pixels differ", name, count)
+            }
+        }
+
+        match result {
+            FixtureResult::Passed | FixtureResult::Updated => {}
+            _ => {
+                failed_count += 1;
+                std::fs::create_dir_all(diff_output_path)
+                    .expect("Unable to create diff output directory");
+                actual
+                    .save(diff_output_path.join(format!("{}.actual.png", name)))
+                    .unwrap();
+
+                if let Ok(reference) = image::open(&reference_path) {
+                    diff_image(&reference.to_rgba8(), &actual, params.tolerance)
+                        .save(diff_output_path.join(format!("{}.diff.png", name)))
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    log::info!(
+        "{} fixtures, {} passed, {} failed",
+        fixtures.len(),
+        fixtures.len() - failed_count,
+        failed_count
+    );
+
+    failed_count == 0
+}
+
+fn collect_fixtures(fixtures_path: &str) -> Vec<PathBuf> {
+    let mut fixtures = std::fs::read_dir(fixtures_path)
+        .expect("Unable to read fixtures directory")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .collect::<Vec<PathBuf>>();
+    fixtures.sort();
+    fixtures
+}
+
+fn compare_with_reference(
+    actual: &RgbaImage,
+    reference_path: &Path,
+    tolerance: u8,
+) -> FixtureResult {
+    let reference = match image::open(reference_path) {
+        Ok(reference) => reference.to_rgba8(),
+        Err(_) => return FixtureResult::MissingReference,
+    };
+
+    if reference.dimensions() != actual.dimensions() {
+        return FixtureResult::SizeMismatch(reference.dimensions(), actual.dimensions());
+    }
+
+    let mismatched_pixels = reference
+        .pixels()
+        .zip(actual.pixels())
+        .filter(|(expected, actual)| !pixel_matches(expected, actual, tolerance))
+        .count();
+
+    if mismatched_pixels > 0 {
+        return FixtureResult::PixelMismatch(mismatched_pixels);
+    }
+
+    FixtureResult::Passed
+}
+
+fn pixel_matches(expected: &Rgba<u8>, actual: &Rgba<u8>, tolerance: u8) -> bool {
+    expected
+        .0
+        .iter()
+        .zip(actual.0.iter())
+        .all(|(expected, actual)| expected.abs_diff(*actual) <= tolerance)
+}
+
+/// Mismatched pixels are painted red on top of a faded copy of the reference image.
+fn diff_image(reference: &RgbaImage, actual: &RgbaImage, tolerance: u8) -> RgbaImage {
+    let (width, height) = reference.dimensions();
+    let (actual_width, actual_height) = actual.dimensions();
+
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let expected = reference.get_pixel(x, y);
+
+        let is_match = x < actual_width
+            && y < actual_height
+            && pixel_matches(expected, actual.get_pixel(x, y), tolerance);
+
+        if !is_match {
+            return Rgba([255, 0, 0, 255]);
+        }
+
+        let [r, g, b, _] = expected.0;
+        let luma = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+        let faded = (255 - (255 - luma) / 4) as u8;
+        Rgba([faded, faded, faded, 255])
+    })
+}